@@ -0,0 +1,1007 @@
+use std::{path::PathBuf, str::FromStr, sync::Arc, time::Duration};
+
+use ratatui::{style::Color, symbols::border};
+use regex::Regex;
+
+use crate::{
+    BORDER_STYLES, COMPLETE_TOLERANCE, DEFAULT_ETA_WINDOW, GAUGE_HEIGHT, LabelAlign, MergeStrategy,
+    POLL_TIMEOUT, TICK_RATE, WORKER_TICK,
+    config::Config,
+    keymap::Keymap,
+    label,
+    parser::{self, ParserKind, ProgressParser},
+    theme::{Theme, parse_color},
+};
+
+pub const USAGE: &str = "\
+Usage: ratatui [OPTIONS] [-- <COMMAND>...]
+
+Arguments:
+  <COMMAND>...     Run this command and track the progress it prints
+
+Options:
+  --stdin          Read progress values from stdin instead of the demo
+  --source [NAME=]PATH
+                   Track a task read from a file or named pipe like --stdin;
+                   repeat for more tasks, named after the path by default
+  --speed <STEP>   Progress added per tick by the demo, in (0, 1] [default: 0.01]
+  --work-ms <MS>   How often the demo's tasks advance, at least 1 [default: 100]
+  --color <COLOR>  Initial gauge color: a name such as cyan, a 256-color index
+                   such as 214, #rrggbb or rgb(r, g, b); c cycles on through
+                   green, yellow, cyan, magenta and red [default: green]
+  --theme <NAME>   Color theme: dark or light [default: dark]
+  --border <NAME>  Borders: plain, rounded, double, thick or none
+                   [default: plain]
+  --orientation <NAME>
+                   Fill gauges left to right (horizontal) or bottom to top
+                   (vertical), side by side [default: horizontal]
+  --tasks <N>      Number of simulated tasks, at least 1 [default: 3]
+  --fps <N>        Most redraws per second, at least 1 [default: 60]
+  --poll-timeout <MS>
+                   Longest the UI waits for input or progress before redrawing
+                   anyway, at least 1 [default: 250]
+  --tick-ms <MS>   How often the UI ticks to redraw and animate, at least 1;
+                   below 10 uses a lot of CPU [default: 250]
+  --curve <EXP>    Ease the gauge fill as progress^EXP, keeping the true
+                   percentage in the label; above 0 [default: 1]
+  --ease <FACTOR>  Animate gauges towards new values, covering this fraction of
+                   the gap per tick; 1 snaps straight there [default: 1]
+  --stall-after <SECS>
+                   Flag a task that makes no progress for this long; 0 never
+                   flags it [default: 5]
+  --eta-window <N> Recent updates the rate and ETA are averaged over; more is
+                   steadier but slower to react, at least 2 [default: 20]
+  --complete-within <PCT>
+                   Count a task as complete once it is this close to 100%, as
+                   progress summed from small steps can settle just short; 0
+                   to 10 [default: 0.01]
+  --average <N>    Draw each gauge at the mean of its last N values, steadying a
+                   jittery source; labels keep the latest, at least 2
+  --gauge-height <ROWS>
+                   Let gauges grow up to this many rows each, sharing the
+                   space between tasks, at least 3 [default: 3]
+  --parser <NAME>  How to read progress from each line: percent (0.42 or 42%),
+                   fraction (12/40 anywhere), bytes (45.2 MB / 100 MB
+                   anywhere) or regex (see --pattern)
+                   [default: regex for a command, otherwise percent]
+  --pattern <RE>   Regex matching progress in the output; its first capture
+                   group is read as a percentage; implies --parser regex
+                   [default: any `NN%`]
+  --label <FORMAT> Gauge label; {pct}, {ratio}, {elapsed}, {rate}, {eta},
+                   {count} (the fraction parser's 12/40), {bytes} (the bytes
+                   parser's 45.2 MB / 100 MB) and {phase} (the active
+                   --phase) are filled in
+                   [default: '{pct}% ({elapsed}) • {rate} • {eta}']
+  --label-align <WHERE>
+                   Put gauge labels at the left, center or right of the bar, or
+                   outside it on the right, giving up to half the width
+                   [default: center]
+  --precision <N>  Decimal places in gauge percentages, 0 to 3 [default: 0]
+  --phase <NAME[=W]>
+                   Split every task into named phases, in order, with the
+                   source reporting each one from 0 to 100%; weighted W times
+                   in the task's progress; may be repeated [default: W=1]
+  --group <NAME=TASK,...>
+                   Gather the named tasks under a gauge called NAME that sums
+                   them up; Enter collapses it. A group can be one of the
+                   tasks of a later --group; may be repeated
+  --milestones <LIST>
+                   Mark these percentages under every gauge, such as 25,50,75;
+                   each lights up once the task passes it
+  --merge <NAME>   How a report from one of several sources feeding a task is
+                   combined with its progress: latest, max, min or average
+                   (the mean of each source's latest report) [default: latest]
+  --weight <NAME=W>
+                   Count the named task W times towards the overall progress;
+                   may be repeated [default: 1 for every task]
+  --duration <TIME>
+                   Fill a single timer over this long, such as 90s, 5m or 1h
+  --listen <ADDR>  Accept `NAME progress: 0.73` lines from remote workers on a
+                   TCP host:port or unix:PATH socket, one task per --worker
+  --worker <NAME>  Name a task --listen reports for; repeat for more workers
+  --watch <PATH>   Track a file another process overwrites with a percentage,
+                   e.g. `echo 42 > PATH`; it need not exist yet
+  --watch-interval <MS>
+                   How often --watch rereads the file [default: 500]
+  --config <PATH>  JSON file mapping task names to gauge colors
+  --keymap <PATH>  JSON file mapping actions to keys, to rebind them
+  --indeterminate  Animate the gauges instead of showing a percentage, for work
+                   of unknown length
+  --compact        Draw a single-line bar instead of the full UI; toggle with v
+  --accessible     Draw gauges with fill patterns as well as color; toggle with p
+  --gradient       Fill gauges with a gradient from red to green, so the color
+                   at the leading edge shows how far along a task is
+  --no-smooth      Fill gauges in whole cells instead of eighths; toggle with f
+  --no-emoji       Show a plain [*] instead of the emoji in the title
+  --clock          Show the time of day at the right of the title
+  --compare        Race exactly two tasks, such as two --source files, saying
+                   which is ahead and by how much; the demo runs two tasks
+  --check-config   Validate the options and config file, print a summary and exit
+  --self-test      Play a scripted run of the demo tasks off screen and print
+                   how many frames it drew and how long they took
+  --json           Print progress as JSON lines instead of drawing the UI
+  --oneshot        Print a single frame, colors included, once the input ends
+                   or the command exits, instead of running the UI
+  --notify         Show a desktop notification when a task completes
+  --beep           Ring the terminal bell each time overall progress passes
+                   another 10%
+  --exit-on-complete
+                   Quit on its own once every task completes, instead of
+                   waiting for q
+  --linger <SECS>  How long --exit-on-complete leaves the finished screen up
+                   [default: 1]
+  --log-file <PATH>
+                   Append each task's progress to PATH with the time, at most
+                   once a second per task
+  --csv <PATH>     Write every progress update to PATH as it arrives, one
+                   timestamp,elapsed_ms,task,progress row each
+  --on-complete <CMD>
+                   Run CMD in the shell whenever a task completes, with the
+                   task's name in $PROGRESS_TASK
+  -h, --help       Print this help";
+
+/// Options parsed from the command line.
+#[derive(Debug)]
+pub struct Args {
+    pub help: bool,
+    pub stdin: bool,
+    pub notify: bool,
+    pub beep: bool,
+    pub exit_on_complete: bool,
+    /// How long the finished screen stays up with `--exit-on-complete`.
+    pub linger: Duration,
+    pub json: bool,
+    pub oneshot: bool,
+    pub check_config: bool,
+    pub self_test: bool,
+    pub no_emoji: bool,
+    pub clock: bool,
+    pub no_smooth: bool,
+    pub compact: bool,
+    pub accessible: bool,
+    pub gradient: bool,
+    pub compare: bool,
+    pub indeterminate: bool,
+    pub speed: f64,
+    /// Initial gauge color, if one was given.
+    pub color: Option<Color>,
+    pub tasks: usize,
+    pub fps: u32,
+    /// Longest wait for an event before redrawing anyway.
+    pub poll_timeout: Duration,
+    /// How often the UI ticks, from `--tick-ms`.
+    pub tick_interval: Duration,
+    /// How often the demo's tasks advance, from `--work-ms`.
+    pub work_interval: Duration,
+    pub curve: f64,
+    pub ease: f64,
+    pub stall_after: Duration,
+    pub eta_window: usize,
+    /// How close to `1.0` counts as complete, as a ratio.
+    pub complete_tolerance: f64,
+    pub average: Option<usize>,
+    pub gauge_height: u16,
+    pub theme: Theme,
+    pub border_set: border::Set,
+    /// Draw the tasks as side-by-side columns that fill upwards.
+    pub vertical: bool,
+    pub parser: ParserKind,
+    pub pattern: Regex,
+    pub label_format: String,
+    /// Decimal places shown in gauge percentages.
+    pub precision: usize,
+    pub config: Option<PathBuf>,
+    pub keymap: Option<PathBuf>,
+    /// Task names and the files or named pipes feeding them.
+    pub sources: Vec<(String, PathBuf)>,
+    /// Length of the `--duration` timer.
+    pub duration: Option<Duration>,
+    /// Socket remote workers report to, and the task names they use.
+    pub listen: Option<String>,
+    pub workers: Vec<String>,
+    /// File polled for a percentage, and how often.
+    pub watch: Option<PathBuf>,
+    pub watch_interval: Duration,
+    /// Positive weights for the overall progress, by task name.
+    pub weights: Vec<(String, f64)>,
+    /// Names and weights of the phases every task goes through, in order.
+    pub phases: Vec<(String, f64)>,
+    /// How reports for the same task are combined.
+    pub merge: MergeStrategy,
+    /// Where gauge labels go.
+    pub label_align: LabelAlign,
+    /// Groups to gather tasks under, and the names of the tasks in each.
+    pub groups: Vec<(String, Vec<String>)>,
+    /// Progress ratios marked under every gauge, in order.
+    pub milestones: Vec<f64>,
+    /// File progress is appended to.
+    pub log_file: Option<PathBuf>,
+    /// File every progress update is written to as CSV.
+    pub csv: Option<PathBuf>,
+    /// Shell command run whenever a task completes.
+    pub on_complete: Option<String>,
+    /// Command to run and track, taken from everything after `--`.
+    pub command: Vec<String>,
+}
+
+impl Default for Args {
+    fn default() -> Self {
+        Self {
+            help: false,
+            stdin: false,
+            notify: false,
+            beep: false,
+            exit_on_complete: false,
+            linger: Duration::from_secs(1),
+            json: false,
+            oneshot: false,
+            check_config: false,
+            self_test: false,
+            no_emoji: false,
+            clock: false,
+            no_smooth: false,
+            compact: false,
+            accessible: false,
+            gradient: false,
+            compare: false,
+            indeterminate: false,
+            speed: 0.01,
+            color: None,
+            tasks: 3,
+            fps: 60,
+            poll_timeout: POLL_TIMEOUT,
+            tick_interval: TICK_RATE,
+            work_interval: WORKER_TICK,
+            curve: 1.0,
+            ease: 1.0,
+            stall_after: Duration::from_secs(5),
+            eta_window: DEFAULT_ETA_WINDOW,
+            complete_tolerance: COMPLETE_TOLERANCE,
+            average: None,
+            gauge_height: GAUGE_HEIGHT,
+            theme: Theme::default(),
+            border_set: border::PLAIN,
+            vertical: false,
+            parser: ParserKind::Percent,
+            pattern: Regex::new(parser::DEFAULT_PATTERN).expect("default pattern is valid"),
+            label_format: label::DEFAULT_LABEL_FORMAT.to_string(),
+            precision: 0,
+            config: None,
+            keymap: None,
+            sources: Vec::new(),
+            weights: Vec::new(),
+            phases: Vec::new(),
+            milestones: Vec::new(),
+            groups: Vec::new(),
+            merge: MergeStrategy::Latest,
+            label_align: LabelAlign::Center,
+            duration: None,
+            listen: None,
+            workers: Vec::new(),
+            watch: None,
+            watch_interval: Duration::from_millis(500),
+            log_file: None,
+            csv: None,
+            on_complete: None,
+            command: Vec::new(),
+        }
+    }
+}
+
+impl Args {
+    /// Parses the arguments following the program name.
+    pub fn parse(args: impl IntoIterator<Item = String>) -> Result<Self, String> {
+        let mut parsed = Self::default();
+        let (mut parser, mut label_given, mut tasks_given) = (None, false, false);
+        let mut args = args.into_iter();
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "-h" | "--help" => parsed.help = true,
+                "--stdin" => parsed.stdin = true,
+                "--notify" => parsed.notify = true,
+                "--beep" => parsed.beep = true,
+                "--exit-on-complete" => parsed.exit_on_complete = true,
+                "--json" => parsed.json = true,
+                "--oneshot" => parsed.oneshot = true,
+                "--check-config" => parsed.check_config = true,
+                "--self-test" => parsed.self_test = true,
+                "--no-emoji" => parsed.no_emoji = true,
+                "--clock" => parsed.clock = true,
+                "--no-smooth" => parsed.no_smooth = true,
+                "--compact" => parsed.compact = true,
+                "--accessible" => parsed.accessible = true,
+                "--gradient" => parsed.gradient = true,
+                "--compare" => parsed.compare = true,
+                "--indeterminate" => parsed.indeterminate = true,
+                "--speed" => {
+                    parsed.speed = parse_value(&arg, args.next(), "a number in (0, 1]", |speed| {
+                        *speed > 0.0 && *speed <= 1.0
+                    })?;
+                }
+                "--color" => parsed.color = Some(parse_color(&value_for(&arg, args.next())?)?),
+                "--theme" => {
+                    let value = value_for(&arg, args.next())?;
+                    parsed.theme = Theme::from_name(&value).ok_or_else(|| {
+                        format!("unknown theme '{value}', expected dark or light")
+                    })?;
+                }
+                "--border" => {
+                    let value = value_for(&arg, args.next())?;
+                    parsed.border_set = BORDER_STYLES
+                        .iter()
+                        .find(|(name, _)| *name == value)
+                        .map(|(_, set)| *set)
+                        .ok_or_else(|| format!("unknown border style '{value}'"))?;
+                }
+                "--orientation" => {
+                    parsed.vertical = match value_for(&arg, args.next())?.as_str() {
+                        "horizontal" => false,
+                        "vertical" => true,
+                        other => return Err(format!("unknown orientation '{other}'")),
+                    };
+                }
+                "--tasks" => {
+                    tasks_given = true;
+                    parsed.tasks =
+                        parse_value(&arg, args.next(), "at least 1", |tasks| *tasks >= 1)?;
+                }
+                "--fps" => {
+                    parsed.fps = parse_value(&arg, args.next(), "at least 1", |fps| *fps >= 1)?;
+                }
+                "--poll-timeout" => {
+                    let millis = parse_value(&arg, args.next(), "at least 1", |ms| *ms >= 1)?;
+                    parsed.poll_timeout = Duration::from_millis(millis);
+                }
+                "--tick-ms" => {
+                    let millis = parse_value(&arg, args.next(), "at least 1", |ms| *ms >= 1)?;
+                    parsed.tick_interval = Duration::from_millis(millis);
+                }
+                "--work-ms" => {
+                    let millis = parse_value(&arg, args.next(), "at least 1", |ms| *ms >= 1)?;
+                    parsed.work_interval = Duration::from_millis(millis);
+                }
+                "--curve" => {
+                    parsed.curve =
+                        parse_value(&arg, args.next(), "a number above 0", |curve: &f64| {
+                            *curve > 0.0 && curve.is_finite()
+                        })?;
+                }
+                "--ease" => {
+                    parsed.ease = parse_value(&arg, args.next(), "a number in (0, 1]", |ease| {
+                        *ease > 0.0 && *ease <= 1.0
+                    })?;
+                }
+                "--stall-after" => {
+                    let secs =
+                        parse_value(&arg, args.next(), "a number of seconds", |secs: &f64| {
+                            *secs >= 0.0 && secs.is_finite()
+                        })?;
+                    parsed.stall_after = Duration::from_secs_f64(secs);
+                }
+                "--eta-window" => {
+                    parsed.eta_window =
+                        parse_value(&arg, args.next(), "at least 2", |window| *window >= 2)?;
+                }
+                "--complete-within" => {
+                    let percent: f64 =
+                        parse_value(&arg, args.next(), "a percentage from 0 to 10", |pct| {
+                            (0.0..=10.0).contains(pct)
+                        })?;
+                    parsed.complete_tolerance = percent / 100.0;
+                }
+                "--average" => {
+                    parsed.average =
+                        Some(parse_value(&arg, args.next(), "at least 2", |window| {
+                            *window >= 2
+                        })?);
+                }
+                "--gauge-height" => {
+                    parsed.gauge_height = parse_value(&arg, args.next(), "at least 3", |rows| {
+                        *rows >= GAUGE_HEIGHT
+                    })?;
+                }
+                "--pattern" => {
+                    let value = value_for(&arg, args.next())?;
+                    parsed.pattern = Regex::new(&value)
+                        .map_err(|err| format!("invalid pattern '{value}': {err}"))?;
+                    parser = parser.or(Some(ParserKind::Regex));
+                }
+                "--parser" => {
+                    let value = value_for(&arg, args.next())?;
+                    parser = Some(ParserKind::from_name(&value).ok_or_else(|| {
+                        format!(
+                            "unknown parser '{value}', expected percent, fraction, bytes or regex"
+                        )
+                    })?);
+                }
+                "--precision" => {
+                    parsed.precision =
+                        parse_value(&arg, args.next(), "0 to 3", |precision| *precision <= 3)?;
+                }
+                "--on-complete" => parsed.on_complete = Some(value_for(&arg, args.next())?),
+                "--label" => {
+                    parsed.label_format = value_for(&arg, args.next())?;
+                    label_given = true;
+                }
+                "--source" => {
+                    let value = value_for(&arg, args.next())?;
+                    parsed.sources.push(match value.split_once('=') {
+                        Some((name, path)) if !name.is_empty() => (name.to_string(), path.into()),
+                        _ => (value.clone(), value.into()),
+                    });
+                }
+                "--weight" => {
+                    let value = value_for(&arg, args.next())?;
+                    let (name, weight) = value
+                        .rsplit_once('=')
+                        .ok_or_else(|| format!("expected NAME=WEIGHT for {arg}, got '{value}'"))?;
+                    let weight = parse_value(
+                        &arg,
+                        Some(weight.to_string()),
+                        "a weight above 0",
+                        |w: &f64| *w > 0.0 && w.is_finite(),
+                    )?;
+                    parsed.weights.push((name.to_string(), weight));
+                }
+                "--duration" => {
+                    let value = value_for(&arg, args.next())?;
+                    parsed.duration = Some(parse_duration(&value).ok_or_else(|| {
+                        format!("invalid value '{value}' for {arg}, expected e.g. 90s, 5m or 1h")
+                    })?);
+                }
+                "--watch" => parsed.watch = Some(value_for(&arg, args.next())?.into()),
+                "--listen" => parsed.listen = Some(value_for(&arg, args.next())?),
+                "--worker" => parsed.workers.push(value_for(&arg, args.next())?),
+                "--log-file" => parsed.log_file = Some(value_for(&arg, args.next())?.into()),
+                "--csv" => parsed.csv = Some(value_for(&arg, args.next())?.into()),
+                "--linger" => {
+                    let secs =
+                        parse_value(&arg, args.next(), "a number of seconds", |secs: &f64| {
+                            *secs >= 0.0 && secs.is_finite()
+                        })?;
+                    parsed.linger = Duration::from_secs_f64(secs);
+                }
+                "--watch-interval" => {
+                    let millis = parse_value(&arg, args.next(), "at least 1", |ms| *ms >= 1)?;
+                    parsed.watch_interval = Duration::from_millis(millis);
+                }
+                "--phase" => {
+                    let value = value_for(&arg, args.next())?;
+                    let (name, weight) = match value.rsplit_once('=') {
+                        Some((name, weight)) => {
+                            let weight = parse_value(
+                                &arg,
+                                Some(weight.to_string()),
+                                "a weight above 0",
+                                |w: &f64| *w > 0.0 && w.is_finite(),
+                            )?;
+                            (name.to_string(), weight)
+                        }
+                        None => (value, 1.0),
+                    };
+                    parsed.phases.push((name, weight));
+                }
+                "--group" => {
+                    let value = value_for(&arg, args.next())?;
+                    let expected = || format!("expected NAME=TASK,TASK for {arg}, got '{value}'");
+                    let (name, members) = value.split_once('=').ok_or_else(expected)?;
+                    let members: Vec<String> = members
+                        .split(',')
+                        .map(str::trim)
+                        .filter(|member| !member.is_empty())
+                        .map(String::from)
+                        .collect();
+                    if name.is_empty() || members.is_empty() {
+                        return Err(expected());
+                    }
+                    parsed.groups.push((name.to_string(), members));
+                }
+                "--milestones" => {
+                    for value in value_for(&arg, args.next())?.split(',') {
+                        let percent = value.trim().trim_end_matches('%').to_string();
+                        let percent = parse_value(&arg, Some(percent), "0 to 100", |pct: &f64| {
+                            (0.0..=100.0).contains(pct)
+                        })?;
+                        parsed.milestones.push(percent / 100.0);
+                    }
+                    parsed.milestones.sort_by(f64::total_cmp);
+                    parsed.milestones.dedup();
+                }
+                "--label-align" => {
+                    let value = value_for(&arg, args.next())?;
+                    parsed.label_align = LabelAlign::from_name(&value).ok_or_else(|| {
+                        format!("unknown label alignment '{value}', expected left, center, right or outside")
+                    })?;
+                }
+                "--merge" => {
+                    let value = value_for(&arg, args.next())?;
+                    parsed.merge = MergeStrategy::from_name(&value).ok_or_else(|| {
+                        format!("unknown merge '{value}', expected latest, max, min or average")
+                    })?;
+                }
+                "--config" => parsed.config = Some(value_for(&arg, args.next())?.into()),
+                "--keymap" => parsed.keymap = Some(value_for(&arg, args.next())?.into()),
+                "--" => {
+                    parsed.command = args.by_ref().collect();
+                    if parsed.command.is_empty() {
+                        return Err("expected a command after '--'".to_string());
+                    }
+                }
+                _ => return Err(format!("unexpected argument '{arg}'")),
+            }
+        }
+        let inputs = [
+            parsed.stdin,
+            !parsed.command.is_empty(),
+            !parsed.sources.is_empty(),
+            parsed.watch.is_some(),
+            parsed.duration.is_some(),
+            parsed.listen.is_some(),
+        ];
+        if inputs.into_iter().filter(|given| *given).count() > 1 {
+            return Err(
+                "only one of --stdin, --source, --watch, --duration, --listen or a command can be used"
+                    .to_string(),
+            );
+        }
+        if parsed.listen.is_some() && parsed.workers.is_empty() {
+            return Err("--listen needs at least one --worker".to_string());
+        }
+        if parsed.listen.is_none() && !parsed.workers.is_empty() {
+            return Err("--worker needs --listen".to_string());
+        }
+        if parsed.json && parsed.duration.is_some() {
+            return Err("--duration needs the interactive UI, not --json".to_string());
+        }
+        if parsed.json && !parsed.phases.is_empty() {
+            return Err("--phase needs the interactive UI, not --json".to_string());
+        }
+        if parsed.merge != MergeStrategy::Latest && (parsed.json || !parsed.phases.is_empty()) {
+            // Phases start over from 0%, which max or average would hold back.
+            return Err("--merge can't be combined with --json or --phase".to_string());
+        }
+        if parsed.json && !parsed.groups.is_empty() {
+            return Err("--group needs the interactive UI, not --json".to_string());
+        }
+        if parsed.json && parsed.log_file.is_some() {
+            return Err("--log-file needs the interactive UI, not --json".to_string());
+        }
+        if parsed.json && parsed.csv.is_some() {
+            return Err("--csv needs the interactive UI, not --json".to_string());
+        }
+        if parsed.oneshot && (parsed.json || parsed.duration.is_some() || parsed.listen.is_some()) {
+            return Err(
+                "--oneshot can't be combined with --json, --duration or --listen".to_string(),
+            );
+        }
+        if parsed.self_test && (!parsed.is_demo() || parsed.json || parsed.oneshot) {
+            return Err(
+                "--self-test plays its own tasks, so it can't be combined with a source, --json or --oneshot"
+                    .to_string(),
+            );
+        }
+        if parsed.compare {
+            if parsed.is_demo() && !tasks_given {
+                parsed.tasks = 2;
+            }
+            let tasks = if !parsed.sources.is_empty() {
+                parsed.sources.len()
+            } else if parsed.listen.is_some() {
+                parsed.workers.len()
+            } else if parsed.is_demo() {
+                parsed.tasks
+            } else {
+                1
+            };
+            if tasks != 2 || !parsed.groups.is_empty() || parsed.json {
+                return Err(
+                    "--compare needs exactly two tasks in the interactive UI, without --group"
+                        .to_string(),
+                );
+            }
+        }
+        // Commands print progress amid other output, which only a regex sifts.
+        parsed.parser = parser.unwrap_or(if parsed.command.is_empty() {
+            ParserKind::Percent
+        } else {
+            ParserKind::Regex
+        });
+        if !label_given {
+            if !parsed.phases.is_empty() {
+                parsed.label_format = label::PHASE_LABEL_FORMAT.to_string();
+            } else if parsed.parser == ParserKind::Fraction {
+                parsed.label_format = label::COUNT_LABEL_FORMAT.to_string();
+            } else if parsed.parser == ParserKind::Bytes {
+                parsed.label_format = label::BYTES_LABEL_FORMAT.to_string();
+            }
+        }
+        Ok(parsed)
+    }
+
+    /// Checks what parsing alone can't, i.e. that every `--source` exists and
+    /// the `--config` and `--keymap` files are valid, and returns what they
+    /// hold.
+    pub fn validate(&self) -> Result<(Config, Keymap), String> {
+        if let Some((_, path)) = self.sources.iter().find(|(_, path)| !path.exists()) {
+            return Err(format!("source '{}' does not exist", path.display()));
+        }
+        let config = match &self.config {
+            Some(path) => Config::load(path)?,
+            None => Config::default(),
+        };
+        let keymap = match &self.keymap {
+            Some(path) => Keymap::load(path)?,
+            None => Keymap::default(),
+        };
+        Ok((config, keymap))
+    }
+
+    /// A fresh instance of the `--parser` for a reader thread.
+    pub fn progress_parser(&self) -> Arc<dyn ProgressParser> {
+        self.parser.build(&self.pattern)
+    }
+
+    /// Describes the validated options for `--check-config`.
+    pub fn summary(&self, config: &Config, keymap: &Keymap) -> String {
+        let source = if !self.command.is_empty() {
+            format!("command '{}'", self.command.join(" "))
+        } else if self.stdin {
+            "stdin".to_string()
+        } else if !self.sources.is_empty() {
+            format!("{} source file(s)", self.sources.len())
+        } else if let Some(duration) = self.duration {
+            format!("timer for {duration:?}")
+        } else if let Some(path) = &self.watch {
+            format!(
+                "watching {} every {:?}",
+                path.display(),
+                self.watch_interval
+            )
+        } else if let Some(addr) = &self.listen {
+            format!("listening on {addr} for {}", self.workers.join(", "))
+        } else {
+            format!("demo with {} task(s) at speed {}", self.tasks, self.speed)
+        };
+        let config = match &self.config {
+            Some(path) => format!("{} ({} task color(s))", path.display(), config.len()),
+            None => "none".to_string(),
+        };
+        let keymap = match &self.keymap {
+            Some(path) => format!("{} ({} key(s) bound)", path.display(), keymap.len()),
+            None => "default".to_string(),
+        };
+        [
+            format!("source: {source}"),
+            format!("parser: {}", self.parser.name()),
+            format!("theme:  {}", self.theme.name),
+            format!("label:  {}", self.label_format),
+            format!("config: {config}"),
+            format!("keymap: {keymap}"),
+            "ok".to_string(),
+        ]
+        .join("\n")
+    }
+
+    /// Whether the built-in simulated tasks are shown, i.e. no other source
+    /// of progress was given.
+    pub fn is_demo(&self) -> bool {
+        self.command.is_empty()
+            && !self.stdin
+            && self.sources.is_empty()
+            && self.watch.is_none()
+            && self.duration.is_none()
+            && self.listen.is_none()
+    }
+}
+
+/// Parses a positive length of time such as `90`, `90s`, `5m` or `1.5h`.
+fn parse_duration(value: &str) -> Option<Duration> {
+    let (number, unit) = match value.find(|c: char| c.is_ascii_alphabetic()) {
+        Some(split) => value.split_at(split),
+        None => (value, "s"),
+    };
+    let scale = match unit {
+        "s" => 1.0,
+        "m" => 60.0,
+        "h" => 3600.0,
+        _ => return None,
+    };
+    let secs = number.parse::<f64>().ok()? * scale;
+    (secs > 0.0 && secs.is_finite()).then(|| Duration::from_secs_f64(secs))
+}
+
+fn value_for(flag: &str, value: Option<String>) -> Result<String, String> {
+    value.ok_or_else(|| format!("{flag} requires a value"))
+}
+
+/// Parses the value following `flag` and checks it with `valid`, describing
+/// the accepted values as `expected` on failure.
+fn parse_value<T: FromStr>(
+    flag: &str,
+    value: Option<String>,
+    expected: &str,
+    valid: impl Fn(&T) -> bool,
+) -> Result<T, String> {
+    let value = value_for(flag, value)?;
+    value
+        .parse()
+        .ok()
+        .filter(valid)
+        .ok_or_else(|| format!("invalid value '{value}' for {flag}, expected {expected}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(list: &[&str]) -> Result<Args, String> {
+        Args::parse(list.iter().map(|arg| arg.to_string()))
+    }
+
+    #[test]
+    fn args_parse_the_demo_options() {
+        assert!(args(&[]).is_ok());
+        let parsed = args(&["--speed", "0.05", "--color", "cyan", "--tasks", "5"]).unwrap();
+        assert_eq!(parsed.speed, 0.05);
+        assert_eq!(parsed.color, Some(Color::Cyan));
+        assert_eq!(parsed.tasks, 5);
+        assert_eq!(
+            args(&["--color", "rgb(255,128,0)"]).unwrap().color,
+            Some(Color::Rgb(255, 128, 0))
+        );
+        assert_eq!(
+            args(&["--color", "214"]).unwrap().color,
+            Some(Color::Indexed(214))
+        );
+        assert!(args(&["--speed", "0"]).is_err());
+        assert!(args(&["--color", "blurple"]).is_err());
+        assert!(args(&["--tasks", "0"]).is_err());
+        assert!(args(&["--tasks"]).is_err());
+        assert!(args(&["--bogus"]).is_err());
+    }
+
+    #[test]
+    fn args_parse_the_timing_options() {
+        assert!(args(&["--fps", "0"]).is_err());
+        assert!(args(&["--poll-timeout", "0"]).is_err());
+        assert!(args(&["--tick-ms", "0"]).is_err());
+        assert!(args(&["--work-ms", "-5"]).is_err());
+        let parsed = args(&["--tick-ms", "16", "--work-ms", "1000"]).unwrap();
+        assert_eq!(parsed.tick_interval, Duration::from_millis(16));
+        assert_eq!(parsed.work_interval, Duration::from_secs(1));
+        assert_eq!(
+            args(&["--poll-timeout", "40"]).unwrap().poll_timeout,
+            Duration::from_millis(40)
+        );
+        assert_eq!(args(&["--fps", "30"]).unwrap().fps, 30);
+    }
+
+    #[test]
+    fn args_parse_the_display_options() {
+        assert!(args(&["--curve", "-1"]).is_err());
+        assert!(args(&["--ease", "1.5"]).is_err());
+        assert_eq!(
+            args(&["--label-align", "outside"]).unwrap().label_align,
+            LabelAlign::Outside
+        );
+        assert!(args(&["--label-align", "middle"]).is_err());
+        assert_eq!(args(&["--gauge-height", "5"]).unwrap().gauge_height, 5);
+        assert!(args(&["--gauge-height", "2"]).is_err());
+        assert_eq!(args(&["--precision", "2"]).unwrap().precision, 2);
+        assert!(args(&["--precision", "4"]).is_err());
+        assert_eq!(args(&["--theme", "light"]).unwrap().theme, Theme::LIGHT);
+        assert!(args(&["--theme", "solarized"]).is_err());
+        assert_eq!(
+            args(&["--border", "none"]).unwrap().border_set,
+            border::EMPTY
+        );
+        assert!(args(&["--border", "dotted"]).is_err());
+        assert!(args(&["--orientation", "vertical"]).unwrap().vertical);
+        assert!(args(&["--orientation", "diagonal"]).is_err());
+    }
+
+    #[test]
+    fn args_parse_weights_milestones_and_groups() {
+        assert_eq!(
+            args(&["--weight", "Download=2.5"]).unwrap().weights,
+            [("Download".to_string(), 2.5)]
+        );
+        assert!(args(&["--weight", "Download"]).is_err());
+        assert!(args(&["--weight", "Download=0"]).is_err());
+        assert_eq!(
+            args(&["--milestones", "75, 25%,50,25"]).unwrap().milestones,
+            [0.25, 0.5, 0.75]
+        );
+        assert!(args(&["--milestones", "50,120"]).is_err());
+        assert_eq!(
+            args(&["--group", "Build=Compile, Link"]).unwrap().groups,
+            [(
+                "Build".to_string(),
+                vec!["Compile".to_string(), "Link".to_string()]
+            )]
+        );
+        assert!(args(&["--group", "Build"]).is_err());
+        assert!(args(&["--group", "Build=,"]).is_err());
+    }
+
+    #[test]
+    fn args_parse_merge_and_phase() {
+        assert_eq!(args(&["--merge", "max"]).unwrap().merge, MergeStrategy::Max);
+        assert!(args(&["--merge", "median"]).is_err());
+        let phased = args(&["--phase", "Download=3", "--phase", "Install"]).unwrap();
+        assert_eq!(
+            phased.phases,
+            [("Download".to_string(), 3.0), ("Install".to_string(), 1.0)]
+        );
+        assert_eq!(phased.label_format, label::PHASE_LABEL_FORMAT);
+        assert!(args(&["--phase", "Download=-1"]).is_err());
+        assert!(args(&["--phase", "Download", "--json"]).is_err());
+        assert!(args(&["--merge", "average", "--phase", "Download"]).is_err());
+    }
+
+    #[test]
+    fn args_parse_the_self_test_and_compare_modes() {
+        assert!(args(&["--self-test", "--tasks", "5"]).unwrap().self_test);
+        assert!(args(&["--self-test", "--stdin"]).is_err());
+        assert_eq!(args(&["--compare"]).unwrap().tasks, 2);
+        assert!(args(&["--compare", "--tasks", "3"]).is_err());
+        assert!(args(&["--compare", "--stdin"]).is_err());
+        assert!(
+            args(&[
+                "--compare",
+                "--listen",
+                "unix:w",
+                "--worker",
+                "a",
+                "--worker",
+                "b"
+            ])
+            .is_ok()
+        );
+    }
+
+    #[test]
+    fn args_parse_the_eta_and_completion_options() {
+        assert_eq!(
+            args(&["--stall-after", "2.5"]).unwrap().stall_after,
+            Duration::from_millis(2500)
+        );
+        assert!(args(&["--stall-after", "-1"]).is_err());
+        assert_eq!(args(&["--eta-window", "50"]).unwrap().eta_window, 50);
+        assert!(args(&["--eta-window", "1"]).is_err());
+        assert_eq!(
+            args(&["--complete-within", "0.5"])
+                .unwrap()
+                .complete_tolerance,
+            0.005
+        );
+        assert!(args(&["--complete-within", "20"]).is_err());
+        assert_eq!(args(&["--average", "5"]).unwrap().average, Some(5));
+        assert!(args(&["--average", "1"]).is_err());
+    }
+
+    #[test]
+    fn args_pick_the_parser_and_its_label_format() {
+        assert!(args(&["--pattern", "(unclosed"]).is_err());
+        assert_eq!(args(&[]).unwrap().parser, ParserKind::Percent);
+        assert_eq!(args(&["--", "make"]).unwrap().parser, ParserKind::Regex);
+        assert_eq!(
+            args(&["--stdin", "--pattern", r"(\d+)"]).unwrap().parser,
+            ParserKind::Regex
+        );
+        let fraction = args(&["--stdin", "--parser", "fraction"]).unwrap();
+        assert_eq!(fraction.parser, ParserKind::Fraction);
+        assert_eq!(fraction.label_format, label::COUNT_LABEL_FORMAT);
+        assert!(args(&["--parser", "bar"]).is_err());
+        assert_eq!(
+            args(&["--stdin", "--parser", "bytes"])
+                .unwrap()
+                .label_format,
+            label::BYTES_LABEL_FORMAT
+        );
+    }
+
+    #[test]
+    fn args_parse_hooks_and_output_files() {
+        assert_eq!(
+            args(&["--on-complete", "say done"])
+                .unwrap()
+                .on_complete
+                .as_deref(),
+            Some("say done")
+        );
+        assert!(args(&["--on-complete"]).is_err());
+        assert_eq!(
+            args(&["--log-file", "run.log"]).unwrap().log_file,
+            Some(PathBuf::from("run.log"))
+        );
+        assert!(args(&["--log-file", "run.log", "--json"]).is_err());
+        assert_eq!(
+            args(&["--csv", "run.csv"]).unwrap().csv,
+            Some(PathBuf::from("run.csv"))
+        );
+        assert!(args(&["--csv", "run.csv", "--json"]).is_err());
+        assert_eq!(
+            args(&["--config", "colors.json"]).unwrap().config,
+            Some(PathBuf::from("colors.json"))
+        );
+    }
+
+    #[test]
+    fn args_parse_sources_and_listeners() {
+        let parsed = args(&["--source", "build=/tmp/build.fifo", "--source", "log.txt"]).unwrap();
+        assert_eq!(
+            parsed.sources,
+            [
+                ("build".to_string(), PathBuf::from("/tmp/build.fifo")),
+                ("log.txt".to_string(), PathBuf::from("log.txt")),
+            ]
+        );
+        assert!(!parsed.is_demo());
+        assert!(args(&["--source", "a", "--stdin"]).is_err());
+        assert!(args(&["--stdin", "--", "make"]).is_err());
+        assert!(args(&["--watch", "a", "--", "make"]).is_err());
+        assert!(args(&["--watch-interval", "0"]).is_err());
+        let parsed = args(&["--listen", "0.0.0.0:7878", "--worker", "a", "--worker", "b"]).unwrap();
+        assert_eq!(parsed.workers, ["a", "b"]);
+        assert!(!parsed.is_demo());
+        assert!(args(&["--listen", "unix:/tmp/progress.sock"]).is_err());
+        assert!(args(&["--worker", "a"]).is_err());
+        assert!(args(&["--listen", "0.0.0.0:7878", "--worker", "a", "--stdin"]).is_err());
+    }
+
+    #[test]
+    fn args_parse_linger_oneshot_and_duration() {
+        assert_eq!(
+            args(&["--linger", "0.5"]).unwrap().linger,
+            Duration::from_millis(500)
+        );
+        assert!(args(&["--linger", "-1"]).is_err());
+        assert!(args(&["--oneshot", "--stdin"]).unwrap().oneshot);
+        assert!(args(&["--oneshot", "--json"]).is_err());
+        assert!(args(&["--oneshot", "--duration", "5m"]).is_err());
+        assert_eq!(
+            args(&["--duration", "1.5m"]).unwrap().duration,
+            Some(Duration::from_secs(90))
+        );
+        assert_eq!(parse_duration("60"), Some(Duration::from_secs(60)));
+        assert_eq!(parse_duration("2h"), Some(Duration::from_secs(7200)));
+        assert_eq!(parse_duration("0s"), None);
+        assert_eq!(parse_duration("5d"), None);
+    }
+
+    #[test]
+    fn args_keep_everything_after_the_separator_for_the_command() {
+        let parsed = args(&["--", "tar", "czf", "--tasks"]).unwrap();
+        let (config, keymap) = parsed.validate().unwrap();
+        assert!(
+            parsed
+                .summary(&config, &keymap)
+                .contains("command 'tar czf --tasks'")
+        );
+        assert_eq!(parsed.command, ["tar", "czf", "--tasks"]);
+        assert_eq!(parsed.tasks, 3);
+    }
+
+    #[test]
+    fn validate_loads_the_config_and_rejects_missing_files() {
+        let path = std::env::temp_dir().join(format!("colors-{}.json", std::process::id()));
+        let check = |config: &str| {
+            std::fs::write(&path, config).unwrap();
+            let flags = ["--check-config", "--config", &path.display().to_string()];
+            let parsed = Args::parse(flags.map(String::from)).unwrap();
+            parsed
+                .validate()
+                .map(|(config, keymap)| parsed.summary(&config, &keymap))
+        };
+
+        let summary = check(r#"{"Compile": "cyan"}"#).unwrap();
+        assert!(summary.contains("demo with 3 task(s)"));
+        assert!(summary.contains("(1 task color(s))"));
+        assert!(check(r#"{"Compile": "blurple"}"#).is_err());
+
+        std::fs::remove_file(&path).unwrap();
+        let missing = Args::parse(["--source".to_string(), path.display().to_string()]).unwrap();
+        assert!(missing.validate().is_err());
+    }
+}
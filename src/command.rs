@@ -0,0 +1,81 @@
+//! Runs a child process and turns its output into progress events.
+
+use std::{
+    io::{self, BufRead, BufReader, Read},
+    process::{Command, Stdio},
+    sync::{Arc, mpsc},
+    thread,
+};
+
+use crate::{
+    Event,
+    parser::{self, ProgressParser},
+};
+
+/// Spawns `command` and reports progress for task `index` from every stdout
+/// and stderr line `parser` reads progress from, followed by an
+/// `Event::Exited` once the child has finished.
+pub fn spawn(
+    command: &[String],
+    parser: Arc<dyn ProgressParser>,
+    index: usize,
+    tx: mpsc::Sender<Event>,
+) -> io::Result<()> {
+    let (program, args) = command
+        .split_first()
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "no command given"))?;
+    let mut child = Command::new(program)
+        .args(args)
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()?;
+
+    let mut readers = Vec::new();
+    if let Some(stdout) = child.stdout.take() {
+        readers.push(read_output(stdout, Arc::clone(&parser), index, tx.clone()));
+    }
+    if let Some(stderr) = child.stderr.take() {
+        readers.push(read_output(stderr, parser, index, tx.clone()));
+    }
+
+    thread::spawn(move || {
+        // Drain the output first so its progress lands before the exit.
+        for reader in readers {
+            let _ = reader.join();
+        }
+        let event = match child.wait() {
+            Ok(status) => Event::Exited(index, status),
+            Err(err) => Event::Error(format!("Waiting for the command failed: {err}")),
+        };
+        let _ = tx.send(event);
+    });
+    Ok(())
+}
+
+fn read_output<R: Read + Send + 'static>(
+    output: R,
+    parser: Arc<dyn ProgressParser>,
+    index: usize,
+    tx: mpsc::Sender<Event>,
+) -> thread::JoinHandle<()> {
+    thread::spawn(move || {
+        // Progress bars often redraw with `\r`, so treat it as a line break too.
+        for chunk in BufReader::new(output).split(b'\n') {
+            let chunk = match chunk {
+                Ok(chunk) => chunk,
+                Err(err) => {
+                    let _ = tx.send(Event::Error(format!(
+                        "Reading the command's output failed: {err}"
+                    )));
+                    return;
+                }
+            };
+            for line in String::from_utf8_lossy(&chunk).split('\r') {
+                if parser::report(parser.as_ref(), line, index, None, &tx).is_err() {
+                    return;
+                }
+            }
+        }
+    })
+}
@@ -0,0 +1,68 @@
+//! Per-task settings read from a JSON file given with `--config`.
+
+use std::{collections::HashMap, fs, path::Path};
+
+use ratatui::style::Color;
+
+use crate::theme::parse_color;
+
+/// Gauge colors keyed by task name, e.g. `{"build": "cyan", "test": "magenta"}`.
+///
+/// Colors are read by `parse_color`: an ANSI name such as `red`, a 256-color
+/// index such as `208`, or `#rrggbb` or `rgb(r, g, b)`.
+#[derive(Debug, Default)]
+pub struct Config {
+    /// Keyed by lowercased task name so lookups ignore case.
+    colors: HashMap<String, Color>,
+}
+
+impl Config {
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let contents = fs::read_to_string(path)
+            .map_err(|err| format!("failed to read {}: {err}", path.display()))?;
+        Self::parse(&contents).map_err(|err| format!("invalid config {}: {err}", path.display()))
+    }
+
+    fn parse(contents: &str) -> Result<Self, String> {
+        let raw: HashMap<String, String> =
+            serde_json::from_str(contents).map_err(|err| err.to_string())?;
+        let colors = raw
+            .into_iter()
+            .map(|(name, value)| {
+                let color =
+                    parse_color(&value).map_err(|err| format!("{err} for task '{name}'"))?;
+                Ok((name.to_lowercase(), color))
+            })
+            .collect::<Result<_, String>>()?;
+        Ok(Self { colors })
+    }
+
+    /// Number of tasks given a color.
+    pub fn len(&self) -> usize {
+        self.colors.len()
+    }
+
+    /// The configured color for the task called `name`, if any.
+    pub fn color_for(&self, name: &str) -> Option<Color> {
+        self.colors.get(&name.to_lowercase()).copied()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_maps_task_names_to_colors() {
+        let config =
+            Config::parse(r##"{"Build": "cyan", "test": "#ff8800", "lint": "rgb(0, 128, 255)"}"##)
+                .unwrap();
+        assert_eq!(config.color_for("build"), Some(Color::Cyan));
+        assert_eq!(config.color_for("Test"), Some(Color::Rgb(255, 136, 0)));
+        assert_eq!(config.color_for("lint"), Some(Color::Rgb(0, 128, 255)));
+        assert_eq!(config.color_for("deploy"), None);
+
+        assert!(Config::parse(r#"{"build": "blurple"}"#).is_err());
+        assert!(Config::parse("not json").is_err());
+    }
+}
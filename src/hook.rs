@@ -0,0 +1,48 @@
+//! Runs the user's `--on-complete` command when a task finishes.
+
+use std::{
+    io,
+    process::{Command, Stdio},
+    sync::{Arc, Mutex},
+    thread,
+};
+
+/// Starts `command` in the shell with `PROGRESS_TASK` set to `task`, without
+/// waiting for it to finish.
+///
+/// The command gets no terminal: its output is discarded and, on Unix, it
+/// runs in its own process group so Ctrl-C in the UI doesn't reach it. Should
+/// it exit unsuccessfully, a message saying so is pushed onto `failures`.
+pub fn spawn(command: &str, task: &str, failures: Arc<Mutex<Vec<String>>>) -> io::Result<()> {
+    let mut shell = if cfg!(windows) {
+        let mut shell = Command::new("cmd");
+        shell.args(["/C", command]);
+        shell
+    } else {
+        let mut shell = Command::new("sh");
+        shell.args(["-c", command]);
+        shell
+    };
+    #[cfg(unix)]
+    std::os::unix::process::CommandExt::process_group(&mut shell, 0);
+
+    let mut child = shell
+        .env("PROGRESS_TASK", task)
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()?;
+    let command = command.to_string();
+    // Reap the child in the background so it never blocks the UI.
+    thread::spawn(move || {
+        let failure = match child.wait() {
+            Ok(status) if status.success() => return,
+            Ok(status) => format!("'{command}' failed: {status}"),
+            Err(err) => format!("'{command}' failed: {err}"),
+        };
+        if let Ok(mut failures) = failures.lock() {
+            failures.push(failure);
+        }
+    });
+    Ok(())
+}
@@ -0,0 +1,136 @@
+//! Non-interactive `--json` mode: one JSON object per progress update.
+
+use std::{
+    io::{self, Write},
+    sync::mpsc,
+    time::Instant,
+};
+
+use serde::Serialize;
+
+use crate::Event;
+
+#[derive(Serialize)]
+struct Update<'a> {
+    task: &'a str,
+    progress: f64,
+    elapsed_ms: u64,
+}
+
+/// Writes a line to `out` for every progress event until each of the tasks
+/// named by `tasks` has finished, returning whether all of them succeeded
+/// and no source failed.
+///
+/// A task finishes when it comes within `tolerance` of `1.0`, which it is
+/// then printed as, or with `await_exit` (for a tracked command) only once it
+/// has exited, so the child is never left running.
+pub fn run(
+    tasks: &[String],
+    rx: mpsc::Receiver<Event>,
+    await_exit: bool,
+    tolerance: f64,
+    mut out: impl Write,
+) -> io::Result<bool> {
+    let start = Instant::now();
+    let mut finished = vec![false; tasks.len()];
+    let mut succeeded = true;
+    while finished.contains(&false) {
+        let Ok(event) = rx.recv() else { break };
+        let (index, progress) = match event {
+            Event::Progress(index, progress) | Event::SourceProgress(index, _, progress) => {
+                (index, progress)
+            }
+            Event::Bytes(index, current, total) if total > 0 => {
+                (index, (current as f64 / total as f64).min(1.0))
+            }
+            Event::Exited(index, status) => {
+                if let Some(finished) = finished.get_mut(index) {
+                    *finished = true;
+                }
+                if !status.success() {
+                    succeeded = false;
+                    continue;
+                }
+                (index, 1.0)
+            }
+            // Errors go to stderr so stdout stays one object per line.
+            Event::Error(message) => {
+                eprintln!("{message}");
+                succeeded = false;
+                continue;
+            }
+            _ => continue,
+        };
+        let Some(task) = tasks.get(index) else {
+            continue;
+        };
+        let progress = if progress >= 1.0 - tolerance {
+            1.0
+        } else {
+            progress
+        };
+        if progress >= 1.0 && !await_exit {
+            // The demo keeps reporting 1.0, so only the first one is printed.
+            if finished[index] {
+                continue;
+            }
+            finished[index] = true;
+        }
+        serde_json::to_writer(
+            &mut out,
+            &Update {
+                task,
+                progress,
+                elapsed_ms: start.elapsed().as_millis() as u64,
+            },
+        )?;
+        writeln!(out)?;
+        out.flush()?;
+    }
+    Ok(succeeded)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::COMPLETE_TOLERANCE;
+
+    #[test]
+    fn run_prints_each_update_until_every_task_completes() {
+        let tasks = ["Download".to_string(), "Test".to_string()];
+        let (tx, rx) = mpsc::channel();
+        for event in [
+            Event::Progress(0, 0.5),
+            Event::Progress(1, 1.0),
+            Event::Tick,
+            Event::Progress(1, 1.0),
+            // Close enough to count, and printed as done.
+            Event::Progress(0, 0.99999),
+            Event::Progress(0, 1.0),
+        ] {
+            tx.send(event).unwrap();
+        }
+
+        let mut out = Vec::new();
+        assert!(run(&tasks, rx, false, COMPLETE_TOLERANCE, &mut out).unwrap());
+        let lines: Vec<serde_json::Value> = String::from_utf8(out)
+            .unwrap()
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        let updates: Vec<(&str, f64)> = lines
+            .iter()
+            .map(|line| {
+                (
+                    line["task"].as_str().unwrap(),
+                    line["progress"].as_f64().unwrap(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            updates,
+            [("Download", 0.5), ("Test", 1.0), ("Download", 1.0)]
+        );
+        assert!(lines.iter().all(|line| line["elapsed_ms"].is_u64()));
+    }
+}
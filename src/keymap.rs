@@ -0,0 +1,294 @@
+//! Key bindings, optionally rebound from a JSON file given with `--keymap`.
+
+use std::{collections::BTreeMap, fs, path::Path};
+
+use crossterm::event::KeyCode;
+use serde::Deserialize;
+
+use crate::Action;
+
+/// The bindings used when no `--keymap` file overrides them.
+pub const DEFAULT_KEYMAP: [(KeyCode, Action); 42] = [
+    (KeyCode::Char('j'), Action::SelectNext),
+    (KeyCode::Char('k'), Action::SelectPrevious),
+    (KeyCode::PageDown, Action::PageDown),
+    (KeyCode::PageUp, Action::PageUp),
+    (KeyCode::Char('c'), Action::ToggleColor),
+    (KeyCode::Char('a'), Action::ToggleAutoColor),
+    (KeyCode::Char('t'), Action::ToggleTheme),
+    (KeyCode::Char('v'), Action::ToggleCompact),
+    (KeyCode::Char('b'), Action::CycleBorder),
+    (KeyCode::Char('f'), Action::ToggleSmooth),
+    (KeyCode::Char('s'), Action::Snapshot),
+    (KeyCode::Char('i'), Action::ToggleInstructions),
+    (KeyCode::Char('p'), Action::ToggleAccessible),
+    (KeyCode::Char(' '), Action::TogglePause),
+    (KeyCode::Char('P'), Action::TogglePauseAll),
+    (KeyCode::Char('r'), Action::Reset),
+    (KeyCode::Char('g'), Action::GoTo),
+    (KeyCode::Right, Action::StepForward),
+    (KeyCode::Left, Action::StepBack),
+    (KeyCode::Enter, Action::ToggleGroup),
+    (KeyCode::Char('x'), Action::DismissError),
+    (KeyCode::Char('u'), Action::Undo),
+    (KeyCode::Char('+'), Action::SpeedUp),
+    (KeyCode::Char('='), Action::SpeedUp),
+    (KeyCode::Up, Action::SpeedUp),
+    (KeyCode::Char('-'), Action::SlowDown),
+    (KeyCode::Down, Action::SlowDown),
+    (KeyCode::Tab, Action::NextTab),
+    (KeyCode::Char('1'), Action::ProgressTab),
+    (KeyCode::Char('2'), Action::LogTab),
+    (KeyCode::Char('3'), Action::StatsTab),
+    // Over the gauges the digits pick a task instead of a tab.
+    (KeyCode::Char('1'), Action::SelectTask(0)),
+    (KeyCode::Char('2'), Action::SelectTask(1)),
+    (KeyCode::Char('3'), Action::SelectTask(2)),
+    (KeyCode::Char('4'), Action::SelectTask(3)),
+    (KeyCode::Char('5'), Action::SelectTask(4)),
+    (KeyCode::Char('6'), Action::SelectTask(5)),
+    (KeyCode::Char('7'), Action::SelectTask(6)),
+    (KeyCode::Char('8'), Action::SelectTask(7)),
+    (KeyCode::Char('9'), Action::SelectTask(8)),
+    (KeyCode::Char('?'), Action::ToggleHelp),
+    (KeyCode::Char('q'), Action::Quit),
+];
+
+/// Names used for actions in a `--keymap` file.
+const ACTION_NAMES: [(&str, Action); 39] = [
+    ("select_next", Action::SelectNext),
+    ("select_previous", Action::SelectPrevious),
+    ("page_down", Action::PageDown),
+    ("page_up", Action::PageUp),
+    ("toggle_color", Action::ToggleColor),
+    ("toggle_auto_color", Action::ToggleAutoColor),
+    ("toggle_theme", Action::ToggleTheme),
+    ("toggle_compact", Action::ToggleCompact),
+    ("cycle_border", Action::CycleBorder),
+    ("toggle_smooth", Action::ToggleSmooth),
+    ("snapshot", Action::Snapshot),
+    ("toggle_instructions", Action::ToggleInstructions),
+    ("toggle_accessible", Action::ToggleAccessible),
+    ("pause", Action::TogglePause),
+    ("pause_all", Action::TogglePauseAll),
+    ("reset", Action::Reset),
+    ("go_to", Action::GoTo),
+    ("step_forward", Action::StepForward),
+    ("step_back", Action::StepBack),
+    ("toggle_group", Action::ToggleGroup),
+    ("dismiss_error", Action::DismissError),
+    ("undo", Action::Undo),
+    ("speed_up", Action::SpeedUp),
+    ("slow_down", Action::SlowDown),
+    ("next_tab", Action::NextTab),
+    ("progress_tab", Action::ProgressTab),
+    ("log_tab", Action::LogTab),
+    ("stats_tab", Action::StatsTab),
+    ("select_task_1", Action::SelectTask(0)),
+    ("select_task_2", Action::SelectTask(1)),
+    ("select_task_3", Action::SelectTask(2)),
+    ("select_task_4", Action::SelectTask(3)),
+    ("select_task_5", Action::SelectTask(4)),
+    ("select_task_6", Action::SelectTask(5)),
+    ("select_task_7", Action::SelectTask(6)),
+    ("select_task_8", Action::SelectTask(7)),
+    ("select_task_9", Action::SelectTask(8)),
+    ("help", Action::ToggleHelp),
+    ("quit", Action::Quit),
+];
+
+/// Named keys accepted in a `--keymap` file; anything else must be a single
+/// character.
+const KEY_NAMES: [(&str, KeyCode); 12] = [
+    ("Space", KeyCode::Char(' ')),
+    ("Enter", KeyCode::Enter),
+    ("Tab", KeyCode::Tab),
+    ("Backspace", KeyCode::Backspace),
+    ("Up", KeyCode::Up),
+    ("Down", KeyCode::Down),
+    ("Left", KeyCode::Left),
+    ("Right", KeyCode::Right),
+    ("Home", KeyCode::Home),
+    ("End", KeyCode::End),
+    ("PgUp", KeyCode::PageUp),
+    ("PgDn", KeyCode::PageDown),
+];
+
+/// Which action each key triggers, e.g. `{"select_next": ["j", "Down"]}`.
+///
+/// Every action listed in the file loses its default keys, so rebinding one
+/// never leaves the old key behind, and a key given to an action is taken
+/// from whatever it did by default. The task jumps only apply over the
+/// gauges, so the file may give a key to one of those there and to another
+/// action on the other tabs, as the defaults do for `1` to `3`. Ctrl-C always quits, whatever the keymap says, and the prompts
+/// opened by `q` and `g` keep their own keys.
+#[derive(Debug, PartialEq)]
+pub struct Keymap {
+    /// In the order they were bound, so the first key for an action is the
+    /// one shown in the footer and help.
+    bindings: Vec<(KeyCode, Action)>,
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum Keys {
+    One(String),
+    Many(Vec<String>),
+}
+
+impl Keymap {
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let contents = fs::read_to_string(path)
+            .map_err(|err| format!("failed to read {}: {err}", path.display()))?;
+        Self::parse(&contents).map_err(|err| format!("invalid keymap {}: {err}", path.display()))
+    }
+
+    fn parse(contents: &str) -> Result<Self, String> {
+        let raw: BTreeMap<String, Keys> =
+            serde_json::from_str(contents).map_err(|err| err.to_string())?;
+        let mut keymap = Self::default();
+        let mut from_file = Vec::new();
+        for (name, keys) in raw {
+            let action = ACTION_NAMES
+                .iter()
+                .find(|(action_name, _)| *action_name == name)
+                .map(|(_, action)| *action)
+                .ok_or_else(|| format!("unknown action '{name}'"))?;
+            let keys = match keys {
+                Keys::One(key) => vec![key],
+                Keys::Many(keys) => keys,
+            };
+            keymap.bindings.retain(|(_, bound)| *bound != action);
+            for key in keys {
+                let code = parse_key(&key)
+                    .ok_or_else(|| format!("unknown key '{key}' for action '{name}'"))?;
+                // A key can only do one thing in each place. It only keeps
+                // doing another elsewhere if the file says so too, so no
+                // default can shadow it.
+                keymap.bindings.retain(|binding| {
+                    binding.0 != code
+                        || binding.1.needs_gauges() != action.needs_gauges()
+                            && from_file.contains(binding)
+                });
+                keymap.bindings.push((code, action));
+                from_file.push((code, action));
+            }
+        }
+        Ok(keymap)
+    }
+
+    /// Number of bound keys.
+    pub fn len(&self) -> usize {
+        self.bindings.len()
+    }
+
+    /// The action `code` triggers, if any, `over_gauges` saying whether the
+    /// progress tab is showing.
+    pub fn action_for(&self, code: KeyCode, over_gauges: bool) -> Option<Action> {
+        let mut actions = self
+            .bindings
+            .iter()
+            .filter(|(bound, _)| *bound == code)
+            .map(|(_, action)| *action);
+        if over_gauges {
+            // A task jump sharing the key wins while it applies.
+            actions.max_by_key(|action| action.needs_gauges())
+        } else {
+            actions.find(|action| !action.needs_gauges())
+        }
+    }
+
+    /// The display name of the first key that triggers `action`, if any,
+    /// either over the gauges or on the other tabs.
+    pub fn key_for(&self, action: Action, over_gauges: bool) -> Option<String> {
+        self.bindings
+            .iter()
+            .find(|(code, bound)| {
+                *bound == action && self.action_for(*code, over_gauges) == Some(action)
+            })
+            .map(|(code, _)| key_name(*code))
+    }
+}
+
+impl Default for Keymap {
+    fn default() -> Self {
+        Self {
+            bindings: DEFAULT_KEYMAP.to_vec(),
+        }
+    }
+}
+
+fn parse_key(key: &str) -> Option<KeyCode> {
+    let mut chars = key.chars();
+    if let (Some(c), None) = (chars.next(), chars.next()) {
+        return Some(KeyCode::Char(c));
+    }
+    KEY_NAMES
+        .iter()
+        .find(|(name, _)| name.eq_ignore_ascii_case(key))
+        .map(|(_, code)| *code)
+}
+
+fn key_name(code: KeyCode) -> String {
+    match KEY_NAMES.iter().find(|(_, named)| *named == code) {
+        Some((name, _)) => name.to_string(),
+        None => code.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_rebinds_actions_over_the_defaults() {
+        let keymap =
+            Keymap::parse(r#"{"select_next": ["n", "Down"], "toggle_color": "x"}"#).unwrap();
+        assert_eq!(
+            keymap.action_for(KeyCode::Char('n'), true),
+            Some(Action::SelectNext)
+        );
+        assert_eq!(
+            keymap.action_for(KeyCode::Down, true),
+            Some(Action::SelectNext)
+        );
+        assert_eq!(keymap.action_for(KeyCode::Char('j'), true), None);
+        assert_eq!(keymap.action_for(KeyCode::Char('c'), true), None);
+        assert_eq!(
+            keymap.key_for(Action::ToggleColor, true).as_deref(),
+            Some("x")
+        );
+        // Untouched actions keep their defaults.
+        assert_eq!(
+            keymap.action_for(KeyCode::Char('q'), true),
+            Some(Action::Quit)
+        );
+        assert_eq!(keymap.key_for(Action::SlowDown, true).as_deref(), Some("-"));
+
+        // A digit given to another action does it over the gauges too.
+        let keymap = Keymap::parse(r#"{"toggle_color": "2"}"#).unwrap();
+        for over_gauges in [false, true] {
+            assert_eq!(
+                keymap.action_for(KeyCode::Char('2'), over_gauges),
+                Some(Action::ToggleColor)
+            );
+        }
+        assert_eq!(keymap.key_for(Action::LogTab, false), None);
+        assert_eq!(keymap.key_for(Action::SelectTask(1), true), None);
+
+        // Unless the file shares it out between the gauges and the rest.
+        let keymap = Keymap::parse(r#"{"select_task_1": "x", "toggle_color": "x"}"#).unwrap();
+        assert_eq!(
+            keymap.action_for(KeyCode::Char('x'), true),
+            Some(Action::SelectTask(0))
+        );
+        assert_eq!(
+            keymap.action_for(KeyCode::Char('x'), false),
+            Some(Action::ToggleColor)
+        );
+        assert_eq!(keymap.key_for(Action::DismissError, false), None);
+
+        assert!(Keymap::parse(r#"{"fly": "f"}"#).is_err());
+        assert!(Keymap::parse(r#"{"quit": "Hyper"}"#).is_err());
+    }
+}
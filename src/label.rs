@@ -0,0 +1,231 @@
+//! User-configurable gauge labels such as `"{pct}% ({elapsed})"`.
+
+use std::time::Duration;
+
+/// Label shown when no `--label` is given.
+pub const DEFAULT_LABEL_FORMAT: &str = "{pct}% ({elapsed}) • {rate} • {eta}";
+
+/// Label shown instead with `--parser fraction`, which knows the counts.
+pub const COUNT_LABEL_FORMAT: &str = "{count} • {pct}% ({elapsed}) • {eta}";
+
+/// Label shown instead with `--parser bytes`.
+pub const BYTES_LABEL_FORMAT: &str = "{bytes} ({pct}%) • {eta}";
+
+/// Label shown instead when tasks are split up with `--phase`.
+pub const PHASE_LABEL_FORMAT: &str = "{phase} • {pct}% ({elapsed}) • {eta}";
+
+/// A label format parsed once up front and filled in on every render.
+///
+/// Recognised placeholders are `{pct}`, `{ratio}`, `{elapsed}`, `{rate}`,
+/// `{eta}`, `{count}`, `{bytes}` and `{phase}`; anything else, including
+/// unknown placeholders, is kept as literal text.
+#[derive(Debug, PartialEq)]
+pub struct LabelTemplate {
+    segments: Vec<Segment>,
+}
+
+#[derive(Debug, PartialEq)]
+enum Segment {
+    Literal(String),
+    Field(Field),
+}
+
+#[derive(Debug, PartialEq)]
+enum Field {
+    Pct,
+    Ratio,
+    Elapsed,
+    Rate,
+    Eta,
+    Count,
+    Bytes,
+    Phase,
+}
+
+/// The values a template can refer to.
+pub struct LabelValues {
+    pub ratio: f64,
+    pub elapsed: Duration,
+    /// Progress per second, as a ratio.
+    pub rate: f64,
+    pub eta: Option<Duration>,
+    /// `(done, total)` as read by the fraction parser, if known.
+    pub count: Option<(u64, u64)>,
+    /// `(current, total)` bytes as read by the bytes parser, if known.
+    pub bytes: Option<(u64, u64)>,
+    /// Name and progress of the active `--phase`, if the task has phases.
+    pub phase: Option<(String, f64)>,
+    /// Decimal places shown by `{pct}` and `{phase}`.
+    pub precision: usize,
+}
+
+impl Default for LabelTemplate {
+    fn default() -> Self {
+        Self::parse(DEFAULT_LABEL_FORMAT)
+    }
+}
+
+impl LabelTemplate {
+    pub fn parse(format: &str) -> Self {
+        let mut segments = Vec::new();
+        let mut literal = String::new();
+        let mut rest = format;
+        while let Some(open) = rest.find('{') {
+            literal.push_str(&rest[..open]);
+            let after = &rest[open..];
+            let field = after.find('}').and_then(|close| {
+                let field = match &after[1..close] {
+                    "pct" => Field::Pct,
+                    "ratio" => Field::Ratio,
+                    "elapsed" => Field::Elapsed,
+                    "rate" => Field::Rate,
+                    "eta" => Field::Eta,
+                    "count" => Field::Count,
+                    "bytes" => Field::Bytes,
+                    "phase" => Field::Phase,
+                    _ => return None,
+                };
+                Some((field, close))
+            });
+            match field {
+                Some((field, close)) => {
+                    if !literal.is_empty() {
+                        segments.push(Segment::Literal(std::mem::take(&mut literal)));
+                    }
+                    segments.push(Segment::Field(field));
+                    rest = &after[close + 1..];
+                }
+                None => {
+                    literal.push('{');
+                    rest = &after[1..];
+                }
+            }
+        }
+        literal.push_str(rest);
+        if !literal.is_empty() {
+            segments.push(Segment::Literal(literal));
+        }
+        Self { segments }
+    }
+
+    pub fn render(&self, values: &LabelValues) -> String {
+        self.segments
+            .iter()
+            .map(|segment| match segment {
+                Segment::Literal(text) => text.clone(),
+                Segment::Field(Field::Pct) => {
+                    format!("{:.*}", values.precision, values.ratio * 100.0)
+                }
+                Segment::Field(Field::Ratio) => format!("{:.2}", values.ratio),
+                Segment::Field(Field::Elapsed) => format!("{:.1}s", values.elapsed.as_secs_f64()),
+                Segment::Field(Field::Rate) => format!("{:.1}%/s", values.rate * 100.0),
+                Segment::Field(Field::Eta) => match values.eta {
+                    Some(left) => format!("~{:.0}s left", left.as_secs_f64()),
+                    None => "ETA: --".to_string(),
+                },
+                Segment::Field(Field::Count) => match values.count {
+                    Some((done, total)) => format!("{done}/{total}"),
+                    None => "--/--".to_string(),
+                },
+                Segment::Field(Field::Bytes) => match values.bytes {
+                    Some((current, total)) => {
+                        format!("{} / {}", human_bytes(current), human_bytes(total))
+                    }
+                    None => "-- / --".to_string(),
+                },
+                Segment::Field(Field::Phase) => match &values.phase {
+                    Some((name, ratio)) => {
+                        format!("{name}… {:.*}%", values.precision, ratio * 100.0)
+                    }
+                    None => "--".to_string(),
+                },
+            })
+            .collect()
+    }
+}
+
+/// Formats a byte count the way download tools do, e.g. `45.2 MB`, in powers
+/// of 1000 with a decimal place only where it says something.
+pub fn human_bytes(bytes: u64) -> String {
+    const UNITS: [&str; 5] = ["KB", "MB", "GB", "TB", "PB"];
+    if bytes < 1000 {
+        return format!("{bytes} B");
+    }
+    let mut size = bytes as f64;
+    let mut unit = "B";
+    for next in UNITS {
+        if size < 1000.0 {
+            break;
+        }
+        size /= 1000.0;
+        unit = next;
+    }
+    let size = format!("{size:.1}");
+    format!("{} {unit}", size.strip_suffix(".0").unwrap_or(&size))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn values() -> LabelValues {
+        LabelValues {
+            ratio: 0.456,
+            elapsed: Duration::from_millis(12_340),
+            rate: 0.032,
+            eta: Some(Duration::from_secs(8)),
+            count: Some((57, 125)),
+            bytes: Some((45_200_000, 100_000_000)),
+            phase: Some(("Extracting".to_string(), 0.34)),
+            precision: 0,
+        }
+    }
+
+    #[test]
+    fn default_templates_match_the_classic_labels() {
+        assert_eq!(
+            LabelTemplate::default().render(&values()),
+            "46% (12.3s) • 3.2%/s • ~8s left"
+        );
+        assert_eq!(
+            LabelTemplate::parse(COUNT_LABEL_FORMAT).render(&values()),
+            "57/125 • 46% (12.3s) • ~8s left"
+        );
+        assert_eq!(
+            LabelTemplate::parse(BYTES_LABEL_FORMAT).render(&values()),
+            "45.2 MB / 100 MB (46%) • ~8s left"
+        );
+        assert_eq!(
+            LabelTemplate::parse(PHASE_LABEL_FORMAT).render(&values()),
+            "Extracting… 34% • 46% (12.3s) • ~8s left"
+        );
+    }
+
+    #[test]
+    fn unknown_and_unclosed_placeholders_render_literally() {
+        let template = LabelTemplate::parse("{ratio} {nope} {eta {pct}%");
+        assert_eq!(template.render(&values()), "0.46 {nope} {eta 46%");
+
+        let stalled = LabelValues {
+            eta: None,
+            ..values()
+        };
+        assert_eq!(LabelTemplate::parse("{eta}").render(&stalled), "ETA: --");
+
+        let precise = LabelValues {
+            precision: 2,
+            ..values()
+        };
+        assert_eq!(LabelTemplate::parse("{pct}%").render(&precise), "45.60%");
+    }
+
+    #[test]
+    fn human_bytes_picks_the_largest_unit_below_a_thousand() {
+        assert_eq!(human_bytes(0), "0 B");
+        assert_eq!(human_bytes(999), "999 B");
+        assert_eq!(human_bytes(1_000), "1 KB");
+        assert_eq!(human_bytes(45_230_000), "45.2 MB");
+        assert_eq!(human_bytes(100_000_000), "100 MB");
+        assert_eq!(human_bytes(3_500_000_000), "3.5 GB");
+    }
+}
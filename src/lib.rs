@@ -0,0 +1,5653 @@
+//! A terminal dashboard tracking the progress of several tasks at once.
+//!
+//! The `ratatui` binary is a thin wrapper around [`run_cli`]. Other programs
+//! can embed the dashboard with [`ProgressApp`], feeding it [`Event`]s from
+//! their own threads.
+
+use std::{
+    cell::{Cell, RefCell},
+    collections::VecDeque,
+    env, fs,
+    io::{self, BufRead, IsTerminal, Write},
+    path::Path,
+    process::{self, ExitStatus},
+    sync::{Arc, Mutex, mpsc},
+    thread,
+    time::{Duration, Instant},
+};
+
+use crossterm::event::{
+    DisableFocusChange, DisableMouseCapture, EnableFocusChange, EnableMouseCapture, KeyCode,
+    KeyEvent, KeyEventKind, KeyModifiers, MouseButton, MouseEventKind,
+};
+
+use ratatui::{
+    layout::{Constraint, Layout, Margin, Rect},
+    prelude::*,
+    style::{Color, Modifier, Style},
+    symbols::border,
+    text::{Line, Span},
+    widgets::{
+        Block, Borders, Clear, Gauge, List, ListItem, Paragraph, Scrollbar, ScrollbarOrientation,
+        ScrollbarState, Sparkline, Tabs, Widget, Wrap,
+    },
+};
+use unicode_width::UnicodeWidthStr;
+
+mod cli;
+mod command;
+mod config;
+mod hook;
+mod json;
+mod keymap;
+mod label;
+mod listen;
+mod notify;
+mod parser;
+mod progress_log;
+mod state;
+mod theme;
+mod timeline;
+
+use keymap::Keymap;
+use label::{LabelTemplate, LabelValues};
+use parser::ProgressParser;
+use progress_log::ProgressLog;
+use state::{SavedState, SavedTask};
+use theme::Theme;
+use timeline::Timeline;
+
+/// Runs the command-line tool with the arguments in `std::env::args`,
+/// returning once the user quits.
+///
+/// Invalid arguments are reported on stderr and exit the process.
+pub fn run_cli() -> io::Result<()> {
+    // Validate everything before touching the terminal so errors print cleanly.
+    let args = match cli::Args::parse(env::args().skip(1)) {
+        Ok(args) => args,
+        Err(err) => {
+            eprintln!("error: {err}\n\n{}", cli::USAGE);
+            process::exit(2);
+        }
+    };
+    if args.help {
+        println!("{}", cli::USAGE);
+        return Ok(());
+    }
+    let (config, keymap) = match args.validate() {
+        Ok(loaded) => loaded,
+        Err(err) => {
+            eprintln!("error: {err}");
+            process::exit(1);
+        }
+    };
+    if args.check_config {
+        println!("{}", args.summary(&config, &keymap));
+        return Ok(());
+    }
+    if args.self_test {
+        return self_test(args.tasks);
+    }
+
+    let simulated = simulated_tasks(args.tasks, args.speed);
+
+    let (tx, rx) = mpsc::channel::<Event>();
+
+    // Spawn the tracked command and listener up front so a bad command or
+    // address fails before the TUI.
+    if !args.command.is_empty()
+        && let Err(err) = command::spawn(&args.command, args.progress_parser(), 0, tx.clone())
+    {
+        eprintln!("error: failed to run '{}': {err}", args.command[0]);
+        process::exit(1);
+    }
+    if let Some(addr) = &args.listen
+        && let Err(err) = listen::spawn(
+            addr,
+            args.workers.clone(),
+            args.progress_parser(),
+            tx.clone(),
+        )
+    {
+        eprintln!("error: failed to listen on {addr}: {err}");
+        process::exit(1);
+    }
+
+    let names: Vec<String> = if !args.command.is_empty() {
+        vec![args.command.join(" ")]
+    } else if args.stdin {
+        vec!["stdin".to_string()]
+    } else if !args.sources.is_empty() {
+        args.sources.iter().map(|(name, _)| name.clone()).collect()
+    } else if let Some(path) = &args.watch {
+        vec![path.display().to_string()]
+    } else if args.listen.is_some() {
+        args.workers.clone()
+    } else if args.duration.is_some() {
+        vec!["Timer".to_string()]
+    } else {
+        simulated.iter().map(|(name, _)| name.clone()).collect()
+    };
+
+    if args.json {
+        let (commands, command_rx) = mpsc::channel();
+        let workers = simulated.iter().map(|(_, step)| (*step, 0.0)).collect();
+        spawn_source(&args, tx, command_rx, workers);
+        let succeeded = json::run(
+            &names,
+            rx,
+            !args.command.is_empty(),
+            args.complete_tolerance,
+            io::stdout().lock(),
+        )?;
+        // Hanging up stops the demo workers.
+        drop(commands);
+        process::exit(if succeeded { 0 } else { 1 });
+    }
+
+    let progress_log = match &args.log_file {
+        Some(path) => match ProgressLog::open(path) {
+            Ok(log) => Some(log),
+            Err(err) => {
+                eprintln!("error: failed to open {}: {err}", path.display());
+                process::exit(1);
+            }
+        },
+        None => None,
+    };
+    let timeline = match &args.csv {
+        Some(path) => match Timeline::create(path) {
+            Ok(timeline) => Some(timeline),
+            Err(err) => {
+                eprintln!("error: failed to create {}: {err}", path.display());
+                process::exit(1);
+            }
+        },
+        None => None,
+    };
+    let mut app = ProgressApp {
+        label_format: LabelTemplate::parse(&args.label_format),
+        precision: args.precision,
+        theme: args.theme,
+        notify_on_complete: args.notify,
+        on_complete: args.on_complete.clone(),
+        progress_log,
+        timeline,
+        beep: args.beep,
+        exit_on_complete: Some(args.linger).filter(|_| args.exit_on_complete),
+        frame_interval: Duration::from_secs(1) / args.fps,
+        poll_timeout: args.poll_timeout,
+        no_emoji: args.no_emoji,
+        show_clock: args.clock,
+        keymap,
+        compact: args.compact,
+        accessible: args.accessible,
+        gradient: args.gradient,
+        milestones: args.milestones.clone(),
+        merge: args.merge,
+        label_align: args.label_align,
+        compare: args.compare,
+        border_set: args.border_set,
+        vertical: args.vertical,
+        smooth: !args.no_smooth,
+        curve: args.curve,
+        // A single frame has no time to animate towards the latest value.
+        ease: Some(args.ease).filter(|ease| *ease < 1.0 && !args.oneshot),
+        duration: args.duration,
+        stall_after: Some(args.stall_after).filter(|after| !after.is_zero()),
+        eta_window: args.eta_window,
+        complete_tolerance: args.complete_tolerance,
+        average_window: args.average,
+        gauge_height: args.gauge_height,
+        ..ProgressApp::new(&names)
+    };
+
+    // The built-in demo resumes and a command is shown against its last run;
+    // other sources report their own progress.
+    let demo = args.is_demo();
+    let state_path = state::default_path().filter(|_| demo || !args.command.is_empty());
+    if let Some(path) = &state_path {
+        app.load_state(path, &args.command);
+    }
+    // A palette color only picks where `c` starts cycling from; any other
+    // color sticks until `c` is pressed, unless the config names one.
+    let color_index = args
+        .color
+        .and_then(|color| PALETTE.iter().position(|palette| *palette == color));
+    let custom_color = args.color.filter(|_| color_index.is_none());
+    for task in &mut app.tasks {
+        if let Some(color_index) = color_index {
+            task.color_index = color_index;
+        }
+        task.color = config.color_for(&task.name).or(custom_color);
+        task.indeterminate = args.indeterminate;
+        if let Some((_, weight)) = args
+            .weights
+            .iter()
+            .find(|(name, _)| name.eq_ignore_ascii_case(&task.name))
+        {
+            task.weight = *weight;
+        }
+        task.phases = args
+            .phases
+            .iter()
+            .map(|(name, weight)| Phase {
+                name: name.clone(),
+                weight: *weight,
+                progress: 0.0,
+            })
+            .collect();
+        // Resumed progress lands part way through the phases.
+        task.fill_phases(task.progress);
+    }
+
+    for (name, members) in &args.groups {
+        if let Err(err) = app.group_named(name, members) {
+            eprintln!("error: {err}");
+            process::exit(1);
+        }
+    }
+
+    if args.oneshot {
+        if let Some(path) = &args.watch {
+            // The file already holds the latest value, so there is nothing to wait for.
+            if let Some(progress) = fs::read_to_string(path)
+                .ok()
+                .and_then(|contents| parse_percentage(&contents))
+            {
+                app.report_progress(0, None, progress);
+            }
+        } else if !demo {
+            let (_commands, command_rx) = mpsc::channel();
+            spawn_source(&args, tx, command_rx, Vec::new());
+            // Sources hang up at the end of their input, and a command once it exits.
+            for event in rx {
+                app.handle_event(event)?;
+            }
+        }
+        app.log_progress(true);
+        if let (Some(Err(err)), Some(path)) = (app.flush_timeline(), &args.csv) {
+            eprintln!("warning: failed to write {}: {err}", path.display());
+        }
+        let (width, height) = crossterm::terminal::size().unwrap_or((80, 24));
+        let mut buffer = Buffer::empty(Rect::new(0, 0, width, height));
+        (&app).render(buffer.area, &mut buffer);
+        print!("{}", buffer_to_ansi(&buffer));
+        return io::stdout().flush();
+    }
+
+    if args.tick_interval < LOW_TICK_RATE {
+        app.set_status(format!(
+            "Warning: ticking every {}ms uses a lot of CPU",
+            args.tick_interval.as_millis()
+        ));
+    }
+
+    install_panic_hook();
+    let mut terminal = ratatui::init();
+    crossterm::execute!(io::stdout(), EnableMouseCapture, EnableFocusChange)?;
+
+    let tx_input = tx.clone();
+    thread::spawn(move || handle_input_event(tx_input));
+
+    let (tx_tick, tick_interval) = (tx.clone(), args.tick_interval);
+    thread::spawn(move || run_tick_thread(tx_tick, tick_interval));
+
+    let workers = simulated
+        .into_iter()
+        .zip(&app.tasks)
+        .map(|((_, step), task)| (step, task.reported_progress()))
+        .collect();
+    let (commands, command_rx) = mpsc::channel();
+    app.controls.commands = Some(commands);
+    spawn_source(&args, tx, command_rx, workers);
+
+    let result = app.run(&mut terminal, rx);
+    let saved = state_path.map(|path| app.save_state(&path, &args.command));
+    let flushed = app.flush_timeline();
+    restore_terminal();
+    if let Some(Err(err)) = saved {
+        eprintln!("warning: failed to save progress: {err}");
+    }
+    if let (Some(Err(err)), Some(path)) = (flushed, &args.csv) {
+        eprintln!("warning: failed to write {}: {err}", path.display());
+    }
+    result
+}
+
+/// Longest `--self-test` waits for its scripted session to quit.
+const SELF_TEST_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Plays a fixed session for `--self-test` on an off-screen terminal the
+/// size of the real one: `tasks` demo tasks ramping to 100% a percent per
+/// tick, the color cycled every tenth of the way, then quitting. The events
+/// are queued up front and played through `ProgressApp::run`, so frames are
+/// drawn and coalesced as they would be live, and the counts and time taken
+/// are printed.
+fn self_test(tasks: usize) -> io::Result<()> {
+    let (width, height) = crossterm::terminal::size().unwrap_or((80, 24));
+    let mut terminal = Terminal::new(CountingBackend {
+        inner: backend::TestBackend::new(width, height),
+        frames: 0,
+    })?;
+    let mut app = ProgressApp::new(
+        simulated_tasks(tasks, 0.0)
+            .into_iter()
+            .map(|(name, _)| name),
+    );
+    let key = |code| Event::Input(KeyEvent::from(code));
+    let mut events = Vec::new();
+    for step in 1..=100 {
+        events.extend((0..tasks).map(|index| Event::Progress(index, f64::from(step) / 100.0)));
+        events.push(Event::Tick);
+        if step % 10 == 0 {
+            events.push(key(KeyCode::Char('c')));
+        }
+    }
+    events.extend([key(KeyCode::Char('q')), key(KeyCode::Char('y'))]);
+    let count = events.len();
+    let (tx, rx) = mpsc::channel();
+    for event in events {
+        let _ = tx.send(event);
+    }
+    // Holding on to `tx` leaves the quit keys as the only way out of the
+    // run, so give up should they go astray.
+    thread::spawn(|| {
+        thread::sleep(SELF_TEST_TIMEOUT);
+        eprintln!("error: self-test didn't quit within {SELF_TEST_TIMEOUT:?}");
+        process::exit(1);
+    });
+
+    let started = Instant::now();
+    app.run(&mut terminal, rx)?;
+    let elapsed = started.elapsed();
+    drop(tx);
+    let frames = terminal.backend().frames;
+
+    if !app.has_exited() || app.tasks.iter().any(|task| !task.completed) {
+        eprintln!("error: self-test quit before every task completed");
+        process::exit(1);
+    }
+    let noun = if frames == 1 { "frame" } else { "frames" };
+    println!(
+        "self-test: {count} events in {frames} {noun} of {width}x{height} in {elapsed:.2?}, \
+         {:.2?} per event",
+        elapsed / count as u32
+    );
+    Ok(())
+}
+
+/// A `TestBackend` that counts the frames drawn on it, for `--self-test`.
+struct CountingBackend {
+    inner: backend::TestBackend,
+    frames: u32,
+}
+
+impl Backend for CountingBackend {
+    fn draw<'a, I>(&mut self, content: I) -> io::Result<()>
+    where
+        I: Iterator<Item = (u16, u16, &'a buffer::Cell)>,
+    {
+        self.inner.draw(content)
+    }
+
+    fn hide_cursor(&mut self) -> io::Result<()> {
+        self.inner.hide_cursor()
+    }
+
+    fn show_cursor(&mut self) -> io::Result<()> {
+        self.inner.show_cursor()
+    }
+
+    fn get_cursor_position(&mut self) -> io::Result<Position> {
+        self.inner.get_cursor_position()
+    }
+
+    fn set_cursor_position<P: Into<Position>>(&mut self, position: P) -> io::Result<()> {
+        self.inner.set_cursor_position(position)
+    }
+
+    fn clear(&mut self) -> io::Result<()> {
+        self.inner.clear()
+    }
+
+    fn clear_region(&mut self, clear_type: backend::ClearType) -> io::Result<()> {
+        self.inner.clear_region(clear_type)
+    }
+
+    fn size(&self) -> io::Result<Size> {
+        self.inner.size()
+    }
+
+    fn window_size(&mut self) -> io::Result<backend::WindowSize> {
+        self.inner.window_size()
+    }
+
+    /// `Terminal::draw` flushes once per frame, whatever changed.
+    fn flush(&mut self) -> io::Result<()> {
+        self.frames += 1;
+        self.inner.flush()
+    }
+}
+
+/// Restores the terminal before the default panic hook prints, so a panic
+/// anywhere leaves a usable shell with the message and backtrace readable.
+fn install_panic_hook() {
+    let default_hook = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |info| {
+        restore_terminal();
+        default_hook(info);
+    }));
+}
+
+/// Undoes everything the UI set up: `ratatui::restore` leaves raw mode and
+/// the alternate screen, but the mouse capture and focus reports are ours.
+fn restore_terminal() {
+    let _ = crossterm::execute!(io::stdout(), DisableMouseCapture, DisableFocusChange);
+    ratatui::restore();
+}
+
+/// Starts the threads feeding progress from stdin, the `--source` files, the
+/// `--watch` file or the demo, whose `workers` give each task's step and
+/// starting progress and which follows the UI's `commands`. A tracked command
+/// already reports through its own threads, so `tx` is just dropped.
+fn spawn_source(
+    args: &cli::Args,
+    tx: mpsc::Sender<Event>,
+    commands: mpsc::Receiver<WorkerCommand>,
+    workers: Vec<(f64, f64)>,
+) {
+    // Commands and listeners were started up front, and timers are driven by
+    // the tick thread instead.
+    if !args.command.is_empty() || args.listen.is_some() || args.duration.is_some() {
+        drop(tx);
+    } else if args.stdin {
+        let parser = args.progress_parser();
+        thread::spawn(move || read_progress(io::stdin().lock(), parser.as_ref(), 0, tx));
+    } else if !args.sources.is_empty() {
+        for (index, (_, path)) in args.sources.iter().enumerate() {
+            let (path, tx, parser) = (path.clone(), tx.clone(), args.progress_parser());
+            // Opening a named pipe blocks until a writer appears, so do it here.
+            thread::spawn(move || match fs::File::open(&path) {
+                Ok(file) => read_progress(io::BufReader::new(file), parser.as_ref(), index, tx),
+                Err(err) => {
+                    let _ = tx.send(Event::Error(format!(
+                        "Can't open {}: {err}",
+                        path.display()
+                    )));
+                }
+            });
+        }
+    } else if let Some(path) = &args.watch {
+        let (path, interval) = (path.clone(), args.watch_interval);
+        thread::spawn(move || watch_progress(&path, interval, 0, tx));
+    } else {
+        let interval = args.work_interval;
+        thread::spawn(move || run_background_thread(tx, commands, workers, interval));
+    }
+}
+
+/// How often the UI is redrawn when no other event arrives, unless
+/// `--tick-ms` says otherwise.
+pub(crate) const TICK_RATE: Duration = Duration::from_millis(250);
+
+/// Ticks more often than this cost a lot of CPU for no visible gain, so
+/// asking for them gets a warning.
+const LOW_TICK_RATE: Duration = Duration::from_millis(10);
+
+/// Longest `run` waits for an event before redrawing anyway, unless
+/// `--poll-timeout` says otherwise.
+pub(crate) const POLL_TIMEOUT: Duration = Duration::from_millis(250);
+
+/// How often the simulated workers advance, unless `--work-ms` says
+/// otherwise.
+pub(crate) const WORKER_TICK: Duration = Duration::from_millis(100);
+
+/// Number of recent progress samples kept per task for rate estimation,
+/// unless `--eta-window` says otherwise.
+pub(crate) const DEFAULT_ETA_WINDOW: usize = 20;
+
+/// How far short of `1.0` progress may settle and still count as complete,
+/// unless `--complete-within` says otherwise. Sums of small steps and
+/// weighted totals tend to land just under.
+pub(crate) const COMPLETE_TOLERANCE: f64 = 1e-4;
+
+/// A task's rate drops to zero once no update has arrived for this long.
+const RATE_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Eased gauges jump the rest of the way once within this of their target.
+const EASE_SNAP: f64 = 0.001;
+
+/// Gauge colors cycled through by the `c` key, in order, as used by the dark
+/// theme; other themes provide their own shade of each.
+pub(crate) const PALETTE: [Color; 5] = [
+    Color::Green,
+    Color::Yellow,
+    Color::Cyan,
+    Color::Magenta,
+    Color::Red,
+];
+
+/// Keys listed in the instructions footer, as `(key, action)`.
+const KEY_HINTS: [(Action, &str); 4] = [
+    (Action::ToggleColor, "toggle color"),
+    (Action::TogglePause, "pause"),
+    (Action::ToggleHelp, "show help"),
+    (Action::Quit, "quit"),
+];
+
+/// Rows of the help popup; each shows the first key bound to its actions.
+const KEY_BINDINGS: [(&[Action], &str); 25] = [
+    (
+        &[Action::SelectNext, Action::SelectPrevious],
+        "Select the next / previous task",
+    ),
+    (
+        &[Action::PageUp, Action::PageDown],
+        "Jump a page of tasks up / down",
+    ),
+    (&[Action::ToggleColor], "Cycle the selected task's color"),
+    (&[Action::ToggleAutoColor], "Toggle auto-color by progress"),
+    (&[Action::ToggleTheme], "Toggle dark / light theme"),
+    (&[Action::ToggleCompact], "Toggle the compact one-line view"),
+    (&[Action::CycleBorder], "Cycle the border style"),
+    (&[Action::ToggleSmooth], "Toggle smooth sub-cell fill"),
+    (&[Action::Snapshot], "Save the screen to a text file"),
+    (
+        &[Action::ToggleInstructions],
+        "Show / hide the key hints footer",
+    ),
+    (
+        &[Action::ToggleAccessible],
+        "Toggle color-blind fill patterns",
+    ),
+    (&[Action::TogglePause], "Pause / resume the selected task"),
+    (&[Action::TogglePauseAll], "Pause / resume every task"),
+    (&[Action::Reset], "Reset the selected task"),
+    (&[Action::GoTo], "Go to a typed percentage"),
+    (
+        &[Action::StepBack, Action::StepForward],
+        "Nudge the selected task by hand",
+    ),
+    (&[Action::ToggleGroup], "Collapse / expand the group"),
+    (&[Action::DismissError], "Dismiss the error banner"),
+    (&[Action::Undo], "Undo the last color / view change"),
+    (
+        &[Action::SpeedUp, Action::SlowDown],
+        "Speed up / slow down the demo",
+    ),
+    (&[Action::NextTab], "Next tab"),
+    (
+        &[Action::ProgressTab, Action::LogTab, Action::StatsTab],
+        "Progress, log or stats tab",
+    ),
+    (
+        &[Action::SelectTask(0), Action::SelectTask(8)],
+        "Jump to task 1 to 9",
+    ),
+    (&[Action::ToggleHelp], "Toggle this help"),
+    (&[Action::Quit], "Quit (asks for confirmation)"),
+];
+
+/// Partial cells for the top of a vertical bar, one to seven eighths full.
+const VERTICAL_EIGHTHS: [&str; 7] = [
+    symbols::bar::ONE_EIGHTH,
+    symbols::bar::ONE_QUARTER,
+    symbols::bar::THREE_EIGHTHS,
+    symbols::bar::HALF,
+    symbols::bar::FIVE_EIGHTHS,
+    symbols::bar::THREE_QUARTERS,
+    symbols::bar::SEVEN_EIGHTHS,
+];
+
+/// Partial cells for the leading edge of a horizontal bar, one to seven
+/// eighths full.
+const HORIZONTAL_EIGHTHS: [&str; 7] = [
+    symbols::block::ONE_EIGHTH,
+    symbols::block::ONE_QUARTER,
+    symbols::block::THREE_EIGHTHS,
+    symbols::block::HALF,
+    symbols::block::FIVE_EIGHTHS,
+    symbols::block::THREE_QUARTERS,
+    symbols::block::SEVEN_EIGHTHS,
+];
+
+/// Red, yellow and green stops that `--gradient` blends between from left to
+/// right; picked to read on dark and light backgrounds alike.
+const GRADIENT: [(u8, u8, u8); 3] = [(215, 50, 40), (230, 190, 30), (40, 175, 70)];
+
+/// Frames cycled through by running tasks' spinners, one per tick.
+const SPINNER: [char; 10] = ['⠋', '⠙', '⠹', '⠸', '⠼', '⠴', '⠦', '⠧', '⠇', '⠏'];
+
+/// Border styles cycled through by the `b` key, by `--border` name;
+/// `none` draws blank borders so the layout doesn't shift.
+pub(crate) const BORDER_STYLES: [(&str, border::Set); 5] = [
+    ("plain", border::PLAIN),
+    ("rounded", border::ROUNDED),
+    ("double", border::DOUBLE),
+    ("thick", border::THICK),
+    ("none", border::EMPTY),
+];
+
+/// Allowed simulation speed multipliers in percent, and the step per key press.
+const SPEED_RANGE: std::ops::RangeInclusive<i32> = 25..=400;
+const SPEED_STEP: i32 = 25;
+
+/// How far `Left` and `Right` nudge the selected task by hand.
+const MANUAL_STEP: f64 = 0.05;
+
+/// Columns each level of a task tree is indented by. Deep trees stop
+/// indenting once gauges are down to half the width.
+const TREE_INDENT: u16 = 2;
+
+/// Rows taken by each task's gauge, including its border, unless
+/// `--gauge-height` asks for more.
+pub(crate) const GAUGE_HEIGHT: u16 = 3;
+
+/// Columns taken by each task's gauge with `--orientation vertical`.
+const VERTICAL_GAUGE_WIDTH: u16 = 8;
+
+/// Below this height the title row is dropped to leave room for the gauges.
+const MIN_HEIGHT_FOR_TITLE: u16 = 8;
+
+/// The smallest area the full layout is drawn in; anything smaller shows a
+/// request to resize instead. The compact view fits anywhere.
+const MIN_WIDTH: u16 = 20;
+const MIN_HEIGHT: u16 = 6;
+
+/// Columns kept for the `--clock` at either side of the title: `HH:MM:SS`
+/// and a space on each side.
+const CLOCK_WIDTH: usize = 10;
+
+/// Below this height the history sparkline is hidden as well.
+const MIN_HEIGHT_FOR_HISTORY: u16 = 11;
+
+/// Upper bound on stored history samples; only as many as fit are drawn.
+const MAX_HISTORY: usize = 512;
+
+/// Most completions kept in the side log, and the log panel's width. The
+/// panel is only shown when the task list would keep `MIN_WIDTH_FOR_LOG`.
+const MAX_COMPLETED_LOG: usize = 50;
+const LOG_WIDTH: u16 = 30;
+const MIN_WIDTH_FOR_LOG: u16 = 40;
+
+/// Most status messages kept for the log tab.
+const MAX_MESSAGES: usize = 200;
+
+/// Most changes `u` can undo, oldest dropped first.
+const MAX_UNDO: usize = 20;
+
+/// Names given to the first simulated tasks; any further ones are numbered.
+const SIMULATED_TASK_NAMES: [&str; 5] = ["Download", "Compile", "Test", "Package", "Deploy"];
+
+/// The dashboard: one gauge per task plus the overall progress, drawn to a
+/// terminal by [`ProgressApp::run`].
+pub struct ProgressApp {
+    exit: bool,
+    /// Color gauges by their progress instead of the palette.
+    auto_color: bool,
+    show_help: bool,
+    /// Show the key hints under the task list; toggled with `i`.
+    show_instructions: bool,
+    /// Set by the first `q`; the app only exits once `y` confirms it.
+    confirm_quit: bool,
+    tasks: Vec<Task>,
+    /// Index of the task that per-task actions apply to, moved with `j`/`k`.
+    selected: usize,
+    controls: WorkerControls,
+    /// Tasks that reached 100% and when, oldest first.
+    completed_log: Vec<(String, Instant)>,
+    /// Mean progress across tasks in percent, recorded on every update.
+    history: VecDeque<u64>,
+    /// Last action taken, shown at the bottom of the screen.
+    status: String,
+    /// The latest failure reported by a source, shown in a red banner until
+    /// dismissed with `x`.
+    error: Option<String>,
+    /// Settings from before each recent undoable action, newest last.
+    undo_stack: Vec<AppSnapshot>,
+    /// While `P` has everything paused, the tasks it paused, for the next
+    /// press to resume.
+    paused_all: Option<Vec<usize>>,
+    /// Every status message, oldest first, for the log tab.
+    messages: VecDeque<String>,
+    /// The view drawn under the title, switched with `Tab` or `1`-`3`.
+    active_tab: Tab,
+    label_format: LabelTemplate,
+    /// Decimal places shown in gauge percentages, from `--precision`.
+    precision: usize,
+    theme: Theme,
+    /// Send a desktop notification whenever a task completes.
+    notify_on_complete: bool,
+    /// Shell command run whenever a task completes, from `--on-complete`.
+    on_complete: Option<String>,
+    /// Failures reported by finished `on_complete` commands, shown on the
+    /// next tick.
+    hook_failures: Arc<Mutex<Vec<String>>>,
+    /// Where progress is recorded, from `--log-file`; dropped after a failed
+    /// write.
+    progress_log: Option<ProgressLog>,
+    /// Where every progress update is written as CSV, from `--csv`; dropped
+    /// after a failed write.
+    timeline: Option<Timeline>,
+    /// Ring the bell at every 10% of overall progress, from `--beep`.
+    beep: bool,
+    /// Highest decile of overall progress the bell has rung for.
+    beep_milestone: usize,
+    /// How long to leave the finished screen up before exiting on its own,
+    /// from `--exit-on-complete` and `--linger`, or `None` to wait for `q`.
+    exit_on_complete: Option<Duration>,
+    /// When every task was last seen finished, to time the linger.
+    finished_at: Option<Instant>,
+    /// On-screen buttons and the action each triggers, rebuilt on every render.
+    buttons: RefCell<Vec<(Rect, Action)>>,
+    /// Index of the first task shown when they don't all fit.
+    scroll_offset: usize,
+    /// How many gauges fit in the task list, recorded on every render.
+    visible_tasks: Cell<usize>,
+    /// The area last rendered into, so snapshots match the screen.
+    rendered_area: Cell<Rect>,
+    /// Counts ticks to drive the spinners and indeterminate animation.
+    spinner_frame: usize,
+    /// A task failed or stalled since a key press last acknowledged it, so
+    /// the borders of troubled tasks blink.
+    alert: bool,
+    /// How many tasks were in trouble at that key press; more re-raise the
+    /// alert.
+    acknowledged_alerts: usize,
+    /// The terminal has focus; redraws are skipped while it doesn't.
+    focused: bool,
+    /// Shortest time between two redraws, from `--fps`.
+    frame_interval: Duration,
+    last_draw: Option<Instant>,
+    /// Longest wait for an event before redrawing anyway, from
+    /// `--poll-timeout`.
+    poll_timeout: Duration,
+    /// Exponent easing the gauge fill from `--curve`, `1.0` for a linear
+    /// fill. Labels always show the true progress.
+    curve: f64,
+    /// How long a task may go without progress before it is flagged, from
+    /// `--stall-after`, or `None` to never flag it.
+    stall_after: Option<Duration>,
+    /// Samples the rate and ETA are averaged over, from `--eta-window`.
+    eta_window: usize,
+    /// How close to `1.0` counts as complete, from `--complete-within`.
+    complete_tolerance: f64,
+    /// Latest values each gauge is drawn at the mean of, from `--average`,
+    /// or `None` to draw the latest alone. Labels and completion always use
+    /// the true progress.
+    average_window: Option<usize>,
+    /// Tallest a horizontal gauge may grow to share out the task list, from
+    /// `--gauge-height`.
+    gauge_height: u16,
+    /// The `g` prompt is open and collecting digits in `input_buffer`.
+    goto_prompt: bool,
+    input_buffer: String,
+    /// Fill gauges in eighths of a cell so slow progress moves smoothly.
+    smooth: bool,
+    /// Borders drawn around every block, from `BORDER_STYLES`. The selected
+    /// gauge is double-lined whatever it is.
+    border_set: border::Set,
+    /// Lay gauges out as columns filling bottom to top, from `--orientation`.
+    vertical: bool,
+    /// Draw gauges with fill patterns so they don't rely on color alone.
+    accessible: bool,
+    /// Fill gauges with a red to green gradient, from `--gradient`.
+    gradient: bool,
+    /// How reports for a task are combined with its progress, from `--merge`.
+    merge: MergeStrategy,
+    /// Where gauge labels go, from `--label-align`.
+    label_align: LabelAlign,
+    /// Race two tasks against each other, from `--compare`: a line under
+    /// their gauges says which is ahead and by how much.
+    compare: bool,
+    /// Progress ratios marked under every gauge, from `--milestones`.
+    milestones: Vec<f64>,
+    /// Draw everything as a single line instead of the full layout.
+    compact: bool,
+    /// Fraction of the remaining distance gauges fill per tick after a jump,
+    /// from `--ease`, or `None` to snap straight to new values.
+    ease: Option<f64>,
+    /// Fill tasks from elapsed time alone over this long, from `--duration`.
+    duration: Option<Duration>,
+    /// Use a plain `[*]` in the title for terminals that misalign the emoji.
+    no_emoji: bool,
+    /// Show the time of day beside the title, from `--clock`.
+    show_clock: bool,
+    keymap: Keymap,
+}
+
+/// Something the user can trigger from the keyboard, through the `Keymap`,
+/// or with an on-screen button.
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Action {
+    SelectNext,
+    SelectPrevious,
+    PageDown,
+    PageUp,
+    ToggleColor,
+    ToggleAutoColor,
+    ToggleTheme,
+    ToggleCompact,
+    CycleBorder,
+    ToggleSmooth,
+    Snapshot,
+    ToggleInstructions,
+    ToggleAccessible,
+    TogglePause,
+    TogglePauseAll,
+    Reset,
+    GoTo,
+    StepForward,
+    StepBack,
+    ToggleGroup,
+    DismissError,
+    Undo,
+    SpeedUp,
+    SlowDown,
+    NextTab,
+    ProgressTab,
+    LogTab,
+    StatsTab,
+    /// Selects the task at this position on screen, counting from zero.
+    SelectTask(u8),
+    ToggleHelp,
+    Quit,
+}
+
+/// How `--merge` combines a task's progress with a new report from one of
+/// several sources feeding it, such as two workers under one `--listen` name.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub(crate) enum MergeStrategy {
+    /// The newest report wins.
+    #[default]
+    Latest,
+    Max,
+    Min,
+    /// The mean of every source's latest report, however often each one
+    /// reports.
+    Average,
+}
+
+impl MergeStrategy {
+    const NAMES: [(&str, MergeStrategy); 4] = [
+        ("latest", MergeStrategy::Latest),
+        ("max", MergeStrategy::Max),
+        ("min", MergeStrategy::Min),
+        ("average", MergeStrategy::Average),
+    ];
+
+    pub(crate) fn from_name(name: &str) -> Option<Self> {
+        Self::NAMES
+            .iter()
+            .find(|(known, _)| *known == name)
+            .map(|(_, strategy)| *strategy)
+    }
+
+    /// Combines a report from `source` with the progress of `task`, `None`
+    /// standing for every report not told apart by source.
+    fn merge(self, task: &mut Task, source: Option<usize>, reported: f64) -> f64 {
+        match self {
+            MergeStrategy::Average => {
+                let latest = &mut task.latest_reports;
+                match latest.iter_mut().find(|(from, _)| *from == source) {
+                    Some((_, progress)) => *progress = reported,
+                    None => latest.push((source, reported)),
+                }
+                latest.iter().map(|(_, progress)| progress).sum::<f64>() / latest.len() as f64
+            }
+            _ if !task.reported => reported,
+            MergeStrategy::Latest => reported,
+            MergeStrategy::Max => task.reported_progress().max(reported),
+            MergeStrategy::Min => task.reported_progress().min(reported),
+        }
+    }
+}
+
+/// Where a horizontal gauge's label goes, from `--label-align`.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub(crate) enum LabelAlign {
+    Left,
+    #[default]
+    Center,
+    Right,
+    /// Beside the bar on its right, which gives up room for it.
+    Outside,
+}
+
+impl LabelAlign {
+    const NAMES: [(&str, LabelAlign); 4] = [
+        ("left", LabelAlign::Left),
+        ("center", LabelAlign::Center),
+        ("right", LabelAlign::Right),
+        ("outside", LabelAlign::Outside),
+    ];
+
+    pub(crate) fn from_name(name: &str) -> Option<Self> {
+        Self::NAMES
+            .iter()
+            .find(|(known, _)| *known == name)
+            .map(|(_, align)| *align)
+    }
+}
+
+/// The views listed in the tab bar under the title.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+enum Tab {
+    /// The gauges, with recent completions beside them.
+    #[default]
+    Progress,
+    /// Every status message so far.
+    Log,
+    /// Figures for the selected task.
+    Stats,
+}
+
+impl Tab {
+    const ALL: [Tab; 3] = [Tab::Progress, Tab::Log, Tab::Stats];
+
+    fn title(self) -> &'static str {
+        match self {
+            Tab::Progress => "Progress",
+            Tab::Log => "Log",
+            Tab::Stats => "Stats",
+        }
+    }
+
+    fn next(self) -> Self {
+        let index = Self::ALL.iter().position(|tab| *tab == self).unwrap_or(0);
+        Self::ALL[(index + 1) % Self::ALL.len()]
+    }
+}
+
+impl Action {
+    /// Button label for actions that have an on-screen button.
+    const BUTTONS: [(Action, &str); 3] = [
+        (Action::ToggleColor, " Toggle Color "),
+        (Action::TogglePause, " Pause "),
+        (Action::Reset, " Reset "),
+    ];
+
+    /// Color and display changes that `u` can take back.
+    const UNDOABLE: [Action; 8] = [
+        Action::ToggleColor,
+        Action::ToggleAutoColor,
+        Action::ToggleTheme,
+        Action::ToggleCompact,
+        Action::CycleBorder,
+        Action::ToggleSmooth,
+        Action::ToggleInstructions,
+        Action::ToggleAccessible,
+    ];
+
+    /// Whether the action only applies over the gauges, leaving its keys to
+    /// other actions on the other tabs.
+    fn needs_gauges(self) -> bool {
+        matches!(self, Action::SelectTask(_))
+    }
+}
+
+/// The settings the undoable actions change, saved before each of them.
+#[derive(Clone, Copy)]
+struct AppSnapshot {
+    /// The selected task, by index, with its palette index and custom color.
+    task: Option<(usize, usize, Option<Color>)>,
+    auto_color: bool,
+    theme: Theme,
+    compact: bool,
+    border_set: border::Set,
+    smooth: bool,
+    show_instructions: bool,
+    accessible: bool,
+}
+
+/// An instruction from the UI to the simulated workers.
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum WorkerCommand {
+    /// Resume advancing the task at the given index.
+    Start(usize),
+    /// Stop advancing the task at the given index until it is started again.
+    Stop(usize),
+    /// Send the task at the given index back to 0%.
+    Reset(usize),
+    /// Carry the task at the given index on from this progress instead.
+    JumpTo(usize, f64),
+    /// Multiply every task's step by this.
+    SetSpeed(f64),
+}
+
+/// The UI's handle on the simulated workers.
+struct WorkerControls {
+    /// Commands for the background thread, or `None` when nothing is
+    /// simulated. Dropping it stops the thread.
+    commands: Option<mpsc::Sender<WorkerCommand>>,
+    /// Multiplier applied to every worker's step, in percent.
+    speed_percent: u32,
+}
+
+impl Default for WorkerControls {
+    fn default() -> Self {
+        Self {
+            commands: None,
+            speed_percent: 100,
+        }
+    }
+}
+
+impl WorkerControls {
+    fn send(&self, command: WorkerCommand) {
+        // With no demo running there's nobody to tell, which is fine.
+        if let Some(commands) = &self.commands {
+            let _ = commands.send(command);
+        }
+    }
+
+    /// Tells the workers to stop, including paused ones that aren't sending.
+    fn shut_down(&mut self) {
+        self.commands = None;
+    }
+
+    /// Changes the speed multiplier by `delta` percent, within `SPEED_RANGE`.
+    fn adjust_speed(&mut self, delta: i32) {
+        let speed =
+            (self.speed_percent as i32 + delta).clamp(*SPEED_RANGE.start(), *SPEED_RANGE.end());
+        self.speed_percent = speed as u32;
+        self.send(WorkerCommand::SetSpeed(self.speed()));
+    }
+
+    fn speed(&self) -> f64 {
+        f64::from(self.speed_percent) / 100.0
+    }
+}
+
+struct Task {
+    name: String,
+    progress: f64,
+    start: Instant,
+    /// When progress last moved forward, to spot stalled tasks.
+    last_progress_at: Instant,
+    /// Recent `(time, progress)` samples, oldest first, capped at the ETA
+    /// window.
+    samples: VecDeque<(Instant, f64)>,
+    /// How the task's command finished, if it is backed by one.
+    exit_status: Option<ExitStatus>,
+    /// Set once progress reaches `1.0`; cleared again by a reset.
+    completed: bool,
+    paused: bool,
+    /// Taken over with `Left`/`Right`, so its source is ignored until `Space`
+    /// resumes it.
+    manual: bool,
+    /// A source has reported since the last reset, so later reports are
+    /// merged with the progress it gave.
+    reported: bool,
+    /// The latest report from each source since the last reset, for
+    /// `--merge average`.
+    latest_reports: Vec<(Option<usize>, f64)>,
+    /// The group the task belongs to, by index.
+    parent: Option<usize>,
+    /// For a group, the indices of the tasks in it, whose progress it sums
+    /// up rather than having a source of its own.
+    children: Vec<usize>,
+    /// A group whose children are hidden from the list.
+    collapsed: bool,
+    /// Index into `PALETTE` (green → yellow → cyan → magenta → red), advanced
+    /// by the `c` key and wrapping back to green.
+    color_index: usize,
+    /// Color from `--config`, used instead of the palette until `c` is pressed.
+    color: Option<Color>,
+    /// The total work is unknown, so an animation is shown instead of a ratio.
+    indeterminate: bool,
+    /// Share of the overall progress this task accounts for, from `--weight`.
+    weight: f64,
+    /// What the gauge fill shows, easing towards `progress` with `--ease`.
+    displayed: f64,
+    /// The latest progress values, oldest first, when `--average` steadies
+    /// the gauge.
+    readings: VecDeque<f64>,
+    /// Progress at the last tick, and how far it moved since the one before,
+    /// shown beside the percentage.
+    tick_progress: f64,
+    tick_delta: f64,
+    /// How far the last run of the same command got before it was cut
+    /// short, drawn dimmed ahead of the live fill.
+    baseline: Option<f64>,
+    /// Highest progress reached since the last reset, marked on the gauge
+    /// when a noisy source has since fallen back.
+    peak: f64,
+    /// `(done, total)` from the fraction parser, shown by `{count}`.
+    count: Option<(u64, u64)>,
+    /// `(current, total)` bytes from `Event::Bytes`, shown by `{bytes}`.
+    bytes: Option<(u64, u64)>,
+    /// Steps from `--phase` the task goes through in order, its progress
+    /// being their weighted sum.
+    phases: Vec<Phase>,
+    /// Index into `phases` of the one the source is reporting on.
+    phase: usize,
+}
+
+/// One named step of a task, such as downloading or installing.
+struct Phase {
+    name: String,
+    /// Share of the task's progress this phase accounts for.
+    weight: f64,
+    progress: f64,
+}
+
+impl Task {
+    fn new(name: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            progress: 0.0,
+            start: Instant::now(),
+            last_progress_at: Instant::now(),
+            samples: VecDeque::with_capacity(DEFAULT_ETA_WINDOW),
+            exit_status: None,
+            completed: false,
+            paused: false,
+            manual: false,
+            reported: false,
+            latest_reports: Vec::new(),
+            parent: None,
+            children: Vec::new(),
+            collapsed: false,
+            color_index: 0,
+            color: None,
+            indeterminate: false,
+            weight: 1.0,
+            displayed: 0.0,
+            readings: VecDeque::new(),
+            tick_progress: 0.0,
+            tick_delta: 0.0,
+            baseline: None,
+            peak: 0.0,
+            count: None,
+            bytes: None,
+            phases: Vec::new(),
+            phase: 0,
+        }
+    }
+
+    /// Moves the task to `progress`, keeping the last `window` samples for
+    /// the rate.
+    fn set_progress(&mut self, progress: f64, window: usize) {
+        if progress > self.progress {
+            self.last_progress_at = Instant::now();
+        }
+        self.progress = progress;
+        self.peak = self.peak.max(progress);
+        self.record_sample(Instant::now(), progress, window);
+        // Jumps and timers move the whole task, so the phases follow along.
+        if !self.phases.is_empty() && (self.phase_total() - progress).abs() > 1e-9 {
+            self.fill_phases(progress);
+        }
+    }
+
+    /// Where the gauge is drawn: the mean of `readings`, or the true progress
+    /// without any or once it reaches 100%, so finishing never lags.
+    fn averaged_progress(&self) -> f64 {
+        if self.readings.is_empty() || self.progress >= 1.0 {
+            return self.progress;
+        }
+        self.readings.iter().sum::<f64>() / self.readings.len() as f64
+    }
+
+    fn record_sample(&mut self, at: Instant, progress: f64, window: usize) {
+        while self.samples.len() >= window.max(1) {
+            self.samples.pop_front();
+        }
+        self.samples.push_back((at, progress));
+    }
+
+    /// Whether the total work is unknown, from `--indeterminate` or a byte
+    /// count without a total.
+    fn is_indeterminate(&self) -> bool {
+        self.indeterminate || matches!(self.bytes, Some((_, 0)))
+    }
+
+    /// The phase the source is currently reporting on, if the task has any.
+    fn active_phase(&self) -> Option<&Phase> {
+        self.phases.get(self.phase)
+    }
+
+    /// What the task's source last reported: the active phase's progress, or
+    /// the task's own without phases.
+    fn reported_progress(&self) -> f64 {
+        self.active_phase()
+            .map_or(self.progress, |phase| phase.progress)
+    }
+
+    /// Records a report on the active phase and returns the task's progress.
+    ///
+    /// Once a phase completes, the first report below `1.0` starts the next
+    /// one, so a source repeating `100%` doesn't skip any phases.
+    fn report_phase(&mut self, progress: f64) -> f64 {
+        let Some(active) = self.active_phase() else {
+            return progress;
+        };
+        if active.progress >= 1.0 && progress < 1.0 && self.phase + 1 < self.phases.len() {
+            self.phase += 1;
+        }
+        self.phases[self.phase].progress = progress;
+        self.phase_total()
+    }
+
+    /// The phases' progress weighted by their share of the task.
+    fn phase_total(&self) -> f64 {
+        let total: f64 = self.phases.iter().map(|phase| phase.weight).sum();
+        let done: f64 = self
+            .phases
+            .iter()
+            .map(|phase| phase.weight * phase.progress)
+            .sum();
+        done / total
+    }
+
+    /// Spreads the task's `progress` over the phases in order, making the
+    /// first unfinished one active.
+    fn fill_phases(&mut self, progress: f64) {
+        let total: f64 = self.phases.iter().map(|phase| phase.weight).sum();
+        let mut left = progress * total;
+        for phase in &mut self.phases {
+            phase.progress = (left / phase.weight).clamp(0.0, 1.0);
+            left -= phase.weight * phase.progress;
+        }
+        self.phase = self
+            .phases
+            .iter()
+            .position(|phase| phase.progress < 1.0)
+            .unwrap_or(self.phases.len().saturating_sub(1));
+    }
+
+    fn reset(&mut self) {
+        self.progress = 0.0;
+        self.displayed = 0.0;
+        self.peak = 0.0;
+        self.count = None;
+        self.bytes = None;
+        self.start = Instant::now();
+        self.last_progress_at = self.start;
+        self.samples.clear();
+        self.readings.clear();
+        self.tick_progress = 0.0;
+        self.tick_delta = 0.0;
+        self.reported = false;
+        self.latest_reports.clear();
+        self.completed = false;
+        self.fill_phases(0.0);
+    }
+
+    /// Whether a running task has gone `threshold` without moving forward.
+    /// Indeterminate tasks never report progress, so they never stall.
+    fn is_stalled(&self, threshold: Duration) -> bool {
+        !self.completed
+            && !self.paused
+            && !self.is_indeterminate()
+            && self.exit_status.is_none()
+            && self.last_progress_at.elapsed() >= threshold
+    }
+
+    /// Average progress per second across the sampled window, or `0.0` when
+    /// progress is going backwards or no update has arrived for `RATE_TIMEOUT`.
+    fn rate(&self) -> f64 {
+        let (Some(&(first_at, first)), Some(&(last_at, last))) =
+            (self.samples.front(), self.samples.back())
+        else {
+            return 0.0;
+        };
+        let secs = last_at.duration_since(first_at).as_secs_f64();
+        if secs <= 0.0 || last <= first || last_at.elapsed() >= RATE_TIMEOUT {
+            return 0.0;
+        }
+        (last - first) / secs
+    }
+
+    /// Extrapolates the time left to reach `1.0` from the sampled rate, or
+    /// `None` when progress is stalled, going backwards or not yet started.
+    fn eta(&self) -> Option<Duration> {
+        let rate = self.rate();
+        if self.progress <= 0.0 || rate <= 0.0 {
+            return None;
+        }
+        Some(Duration::from_secs_f64(
+            (1.0 - self.progress).max(0.0) / rate,
+        ))
+    }
+}
+
+/// Everything that wakes [`ProgressApp::run`], sent from the input, tick and
+/// progress threads.
+pub enum Event {
+    Input(crossterm::event::KeyEvent),
+    Mouse(crossterm::event::MouseEvent),
+    /// Progress update for the task at the given index.
+    Progress(usize, f64),
+    /// Progress update for the task at the first index from the source
+    /// numbered second, one of several that may feed it, such as workers
+    /// connecting to `--listen` under the same name.
+    SourceProgress(usize, usize, f64),
+    /// The `done/total` counts behind the next progress update for the task
+    /// at the given index.
+    Count(usize, u64, u64),
+    /// Bytes done and in total for the task at the given index, its progress
+    /// being their ratio. A total of `0` means it isn't known yet, so the
+    /// task animates as if indeterminate.
+    Bytes(usize, u64, u64),
+    /// Periodic redraw so time-based displays keep updating.
+    Tick,
+    /// The terminal was resized and needs a redraw.
+    Resize,
+    /// The terminal window gained or lost focus.
+    FocusGained,
+    FocusLost,
+    /// The tracked command behind the task at the given index has exited.
+    Exited(usize, ExitStatus),
+    /// A source failed, such as a `--source` file that can't be opened, and
+    /// won't report any more.
+    Error(String),
+}
+
+fn handle_input_event(tx: mpsc::Sender<Event>) {
+    while let Ok(event) = crossterm::event::read() {
+        let event = match event {
+            crossterm::event::Event::Key(key_event) => Event::Input(key_event),
+            crossterm::event::Event::Mouse(mouse_event) => Event::Mouse(mouse_event),
+            crossterm::event::Event::Resize(_, _) => Event::Resize,
+            crossterm::event::Event::FocusGained => Event::FocusGained,
+            crossterm::event::Event::FocusLost => Event::FocusLost,
+            _ => continue,
+        };
+        if tx.send(event).is_err() {
+            break;
+        }
+    }
+}
+
+fn run_tick_thread(tx: mpsc::Sender<Event>, interval: Duration) {
+    loop {
+        thread::sleep(interval);
+        if tx.send(Event::Tick).is_err() {
+            break;
+        }
+    }
+}
+
+/// Feeds the task at `index` from lines of `reader`, skipping any `parser`
+/// finds no progress in, and completes it once the reader is closed. A
+/// failed read is reported as an error instead, leaving the task unfinished.
+fn read_progress(
+    reader: impl BufRead,
+    parser: &dyn ProgressParser,
+    index: usize,
+    tx: mpsc::Sender<Event>,
+) {
+    for line in reader.lines() {
+        let line = match line {
+            Ok(line) => line,
+            Err(err) => {
+                let _ = tx.send(Event::Error(format!("Reading progress failed: {err}")));
+                return;
+            }
+        };
+        if parser::report(parser, &line, index, None, &tx).is_err() {
+            return;
+        }
+    }
+    let _ = tx.send(Event::Progress(index, 1.0));
+}
+
+/// Rereads `path` every `interval` and reports the percentage it holds for
+/// the task at `index` whenever it changes. A missing file or unparsable
+/// contents are skipped, which leaves the task to show as stalled, but any
+/// other failure to read it is reported as an error once.
+fn watch_progress(path: &Path, interval: Duration, index: usize, tx: mpsc::Sender<Event>) {
+    let mut last = None;
+    let mut failed = false;
+    loop {
+        let event = match fs::read_to_string(path) {
+            Ok(contents) => {
+                failed = false;
+                parse_percentage(&contents)
+                    .filter(|progress| last != Some(*progress))
+                    .map(|progress| {
+                        last = Some(progress);
+                        Event::Progress(index, progress)
+                    })
+            }
+            Err(err) if err.kind() != io::ErrorKind::NotFound && !failed => {
+                failed = true;
+                Some(Event::Error(format!(
+                    "Can't read {}: {err}",
+                    path.display()
+                )))
+            }
+            Err(_) => None,
+        };
+        if let Some(event) = event
+            && tx.send(event).is_err()
+        {
+            return;
+        }
+        thread::sleep(interval);
+    }
+}
+
+/// Parses a percentage such as `42` or `42.5%` into a ratio.
+fn parse_percentage(text: &str) -> Option<f64> {
+    let text = text.trim();
+    let percent: f64 = text.strip_suffix('%').unwrap_or(text).trim().parse().ok()?;
+    (0.0..=100.0).contains(&percent).then_some(percent / 100.0)
+}
+
+/// Builds `count` simulated tasks as `(name, step per tick)`, each slower than
+/// the last so the demo shows bars moving at different rates.
+fn simulated_tasks(count: usize, speed: f64) -> Vec<(String, f64)> {
+    (0..count)
+        .map(|index| {
+            let name = match SIMULATED_TASK_NAMES.get(index) {
+                Some(name) => name.to_string(),
+                None => format!("Task {}", index + 1),
+            };
+            (name, speed / (index + 1) as f64)
+        })
+        .collect()
+}
+
+/// A demo task as the background thread sees it.
+struct SimulatedTask {
+    step: f64,
+    progress: f64,
+    paused: bool,
+}
+
+/// Advances one simulated task per `(step per tick, starting progress)` pair
+/// every `interval`, following the UI's `commands` in between, until the UI
+/// hangs up on either channel.
+fn run_background_thread(
+    tx: mpsc::Sender<Event>,
+    commands: mpsc::Receiver<WorkerCommand>,
+    workers: Vec<(f64, f64)>,
+    interval: Duration,
+) {
+    let mut tasks: Vec<SimulatedTask> = workers
+        .into_iter()
+        .map(|(step, progress)| SimulatedTask {
+            step,
+            progress,
+            paused: false,
+        })
+        .collect();
+    let mut speed = 1.0;
+    let mut next_tick = Instant::now() + interval;
+    loop {
+        // Waiting on the channel rather than sleeping applies commands at once.
+        let command =
+            match commands.recv_timeout(next_tick.saturating_duration_since(Instant::now())) {
+                Ok(command) => command,
+                Err(mpsc::RecvTimeoutError::Timeout) => {
+                    next_tick = Instant::now() + interval;
+                    for (index, task) in tasks.iter_mut().enumerate() {
+                        if task.paused {
+                            continue;
+                        }
+                        task.progress = (task.progress + task.step * speed).min(1.0);
+                        // The UI has closed, so there is nobody left to report to.
+                        if tx.send(Event::Progress(index, task.progress)).is_err() {
+                            return;
+                        }
+                    }
+                    continue;
+                }
+                Err(mpsc::RecvTimeoutError::Disconnected) => return,
+            };
+        let index = match command {
+            WorkerCommand::SetSpeed(multiplier) => {
+                speed = multiplier;
+                continue;
+            }
+            WorkerCommand::Start(index)
+            | WorkerCommand::Stop(index)
+            | WorkerCommand::Reset(index)
+            | WorkerCommand::JumpTo(index, _) => index,
+        };
+        let Some(task) = tasks.get_mut(index) else {
+            continue;
+        };
+        match command {
+            WorkerCommand::Start(_) => task.paused = false,
+            WorkerCommand::Stop(_) => task.paused = true,
+            WorkerCommand::Reset(_) => task.progress = 0.0,
+            WorkerCommand::JumpTo(_, target) => task.progress = target,
+            WorkerCommand::SetSpeed(_) => {}
+        }
+    }
+}
+
+impl Default for ProgressApp {
+    /// An app with no tasks yet and every setting at its default.
+    fn default() -> Self {
+        Self {
+            exit: false,
+            auto_color: false,
+            show_help: false,
+            show_instructions: true,
+            confirm_quit: false,
+            tasks: Vec::new(),
+            selected: 0,
+            controls: WorkerControls::default(),
+            completed_log: Vec::new(),
+            history: VecDeque::new(),
+            status: String::new(),
+            error: None,
+            undo_stack: Vec::new(),
+            paused_all: None,
+            messages: VecDeque::new(),
+            active_tab: Tab::default(),
+            label_format: LabelTemplate::default(),
+            precision: 0,
+            theme: Theme::default(),
+            notify_on_complete: false,
+            on_complete: None,
+            hook_failures: Arc::default(),
+            progress_log: None,
+            timeline: None,
+            beep: false,
+            beep_milestone: 0,
+            exit_on_complete: None,
+            finished_at: None,
+            buttons: RefCell::default(),
+            scroll_offset: 0,
+            visible_tasks: Cell::default(),
+            rendered_area: Cell::default(),
+            spinner_frame: 0,
+            alert: false,
+            acknowledged_alerts: 0,
+            // Terminals only report focus changes, so assume it to start.
+            focused: true,
+            frame_interval: Duration::ZERO,
+            last_draw: None,
+            poll_timeout: POLL_TIMEOUT,
+            curve: 1.0,
+            stall_after: None,
+            eta_window: DEFAULT_ETA_WINDOW,
+            complete_tolerance: COMPLETE_TOLERANCE,
+            average_window: None,
+            gauge_height: GAUGE_HEIGHT,
+            goto_prompt: false,
+            input_buffer: String::new(),
+            smooth: false,
+            border_set: border::Set::default(),
+            vertical: false,
+            accessible: false,
+            gradient: false,
+            merge: MergeStrategy::default(),
+            label_align: LabelAlign::default(),
+            compare: false,
+            milestones: Vec::new(),
+            compact: false,
+            ease: None,
+            duration: None,
+            no_emoji: false,
+            show_clock: false,
+            keymap: Keymap::default(),
+        }
+    }
+}
+
+impl ProgressApp {
+    /// An app tracking one task per name, each starting at 0%.
+    pub fn new<S: Into<String>>(names: impl IntoIterator<Item = S>) -> Self {
+        let tasks: Vec<Task> = names
+            .into_iter()
+            .map(|name| Task::new(&name.into()))
+            .collect();
+        Self {
+            tasks,
+            ..Default::default()
+        }
+    }
+
+    /// Sets the progress of the task at `index` to `progress`, from `0.0` to
+    /// `1.0`. Out-of-range indices are ignored, values outside `0.0..=1.0`
+    /// are clamped and NaN or infinite ones dropped.
+    pub fn set_progress(&mut self, index: usize, progress: f64) {
+        if let Some(progress) = self.checked_progress(index, progress) {
+            self.update_progress(index, progress);
+        }
+    }
+
+    /// Gathers the tasks at `children` under a new group called `name` and
+    /// returns its index. A group has no source of its own: its gauge sums
+    /// up its children's progress, weighted like the overall progress, and
+    /// groups can be gathered into groups in turn. Children out of range or
+    /// already in a group are skipped.
+    pub fn group(&mut self, name: impl Into<String>, children: &[usize]) -> usize {
+        let index = self.tasks.len();
+        let mut group = Task::new(&name.into());
+        for &child in children {
+            if let Some(task) = self.tasks.get_mut(child)
+                && task.parent.is_none()
+            {
+                task.parent = Some(index);
+                group.children.push(child);
+            }
+        }
+        self.tasks.push(group);
+        let progress = self.aggregate_progress(index);
+        self.record_progress(index, progress);
+        index
+    }
+
+    /// Switches the selected gauge to the next palette color, as `c` does.
+    pub fn toggle_color(&mut self) {
+        self.perform(Action::ToggleColor);
+    }
+
+    /// The progress of the task at `index`, from `0.0` to `1.0`.
+    pub fn progress(&self, index: usize) -> Option<f64> {
+        self.tasks.get(index).map(|task| task.progress)
+    }
+
+    /// The color the task at `index` is currently drawn in.
+    pub fn gauge_color(&self, index: usize) -> Option<Color> {
+        self.tasks.get(index).map(|task| self.task_color(task))
+    }
+
+    /// Whether the app has finished, because the user quit or every sender
+    /// was dropped.
+    pub fn has_exited(&self) -> bool {
+        self.exit
+    }
+
+    /// Draws to `terminal` and handles events from `rx` until the user quits
+    /// or every sender is dropped.
+    ///
+    /// Events already queued on `rx` are handled in order, so a test can
+    /// script a whole session up front and run it against a `TestBackend`.
+    /// Each `Event::Tick` redraws too, so a paused or idle app still moves
+    /// its clock, spinner and ETA without spinning on the channel.
+    pub fn run<B: Backend>(
+        &mut self,
+        terminal: &mut Terminal<B>,
+        rx: mpsc::Receiver<Event>,
+    ) -> io::Result<()> {
+        while !self.exit {
+            let frame_at = Instant::now();
+            // Nobody is looking, so save the work until focus comes back.
+            if self.focused {
+                terminal.draw(|f| self.draw(f))?;
+                self.last_draw = Some(frame_at);
+            }
+            // Wake up now and then even with nothing to handle, so elapsed
+            // times and the clock keep moving on an idle screen.
+            match rx.recv_timeout(self.poll_timeout) {
+                Ok(event) => self.handle_event(event)?,
+                Err(mpsc::RecvTimeoutError::Timeout) => continue,
+                // Every sender is gone, so nothing can update the UI any more.
+                Err(mpsc::RecvTimeoutError::Disconnected) => {
+                    self.exit = true;
+                    break;
+                }
+            }
+            // Fold in everything else that arrives before the next frame is
+            // due, so a chatty source can't force a redraw per update.
+            let next_frame = frame_at + self.frame_interval;
+            while !self.exit {
+                match rx.recv_timeout(next_frame.saturating_duration_since(Instant::now())) {
+                    Ok(event) => self.handle_event(event)?,
+                    Err(mpsc::RecvTimeoutError::Timeout) => break,
+                    Err(mpsc::RecvTimeoutError::Disconnected) => self.exit = true,
+                }
+            }
+        }
+        // Whatever changed since the last tick still belongs in the log.
+        self.log_progress(true);
+        self.controls.shut_down();
+        Ok(())
+    }
+
+    fn handle_event(&mut self, event: Event) -> io::Result<()> {
+        match event {
+            Event::Input(key_event) => self.handle_key(key_event)?,
+            Event::Mouse(mouse_event) => self.handle_mouse(mouse_event),
+            Event::Progress(index, p) => self.report_progress(index, None, p),
+            Event::SourceProgress(index, source, p) => self.report_progress(index, Some(source), p),
+            Event::Tick => {
+                self.spinner_frame = self.spinner_frame.wrapping_add(1);
+                self.update_alert();
+                self.report_hook_failures();
+                self.advance_timers();
+                self.ease_displayed_progress();
+                for task in &mut self.tasks {
+                    task.tick_delta = task.progress - task.tick_progress;
+                    task.tick_progress = task.progress;
+                }
+                self.log_progress(false);
+            }
+            Event::Resize => {}
+            Event::FocusGained => self.focused = true,
+            Event::FocusLost => self.focused = false,
+            Event::Count(index, done, total) => {
+                if let Some(task) = self.tasks.get_mut(index) {
+                    task.count = Some((done, total));
+                }
+            }
+            Event::Bytes(index, current, total) => {
+                if let Some(task) = self.tasks.get_mut(index) {
+                    task.bytes = Some((current, total));
+                }
+                if total > 0 {
+                    self.report_progress(index, None, current as f64 / total as f64);
+                }
+            }
+            Event::Exited(index, status) => {
+                if status.success() {
+                    self.update_progress(index, 1.0);
+                }
+                if let Some(task) = self.tasks.get_mut(index) {
+                    task.exit_status = Some(status);
+                }
+            }
+            Event::Error(message) => {
+                self.set_status(&message);
+                self.error = Some(message);
+            }
+        }
+        self.exit_once_complete();
+        Ok(())
+    }
+
+    /// With `--exit-on-complete`, exits once every task has been finished
+    /// for the linger, so the final frame is seen first.
+    fn exit_once_complete(&mut self) {
+        let Some(linger) = self.exit_on_complete else {
+            return;
+        };
+        // A failed command won't get any further, so it counts as finished.
+        let finished = !self.tasks.is_empty()
+            && self
+                .tasks
+                .iter()
+                .all(|task| task.completed || task.exit_status.is_some());
+        if !finished {
+            self.finished_at = None;
+            return;
+        }
+        let since = *self.finished_at.get_or_insert_with(Instant::now);
+        if since.elapsed() >= linger {
+            self.exit = true;
+        }
+    }
+
+    /// Clamps `progress` from an untrusted source into `0.0..=1.0`, or drops
+    /// it when it isn't a number at all, warning in the status line either
+    /// way.
+    fn checked_progress(&mut self, index: usize, progress: f64) -> Option<f64> {
+        let valid = (0.0..=1.0).contains(&progress);
+        if !valid && let Some(task) = self.tasks.get(index) {
+            let action = if progress.is_finite() {
+                "Clamped"
+            } else {
+                "Ignored"
+            };
+            let message = format!("{action} invalid progress {progress} for {}", task.name);
+            self.set_status(message);
+        }
+        progress.is_finite().then(|| progress.clamp(0.0, 1.0))
+    }
+
+    /// Records progress reported by a task's source, which with `--phase`
+    /// covers just the active phase. `source` tells several sources feeding
+    /// the task apart, for `--merge`.
+    fn report_progress(&mut self, index: usize, source: Option<usize>, progress: f64) {
+        let Some(progress) = self.checked_progress(index, progress) else {
+            return;
+        };
+        // Snapped here too so a phase that settles just short still ends.
+        let progress = self.snap_complete(progress);
+        let Some(task) = self
+            .tasks
+            .get_mut(index)
+            .filter(|task| !task.manual && task.children.is_empty())
+        else {
+            return;
+        };
+        let progress = self.merge.merge(task, source, progress);
+        task.reported = true;
+        let finishing_phase = progress >= 1.0
+            && task.phase + 1 < task.phases.len()
+            && task
+                .active_phase()
+                .is_some_and(|phase| phase.progress < 1.0);
+        let progress = task.report_phase(progress);
+        if finishing_phase {
+            // Start a simulated worker over on the next phase.
+            self.controls.send(WorkerCommand::Reset(index));
+        }
+        self.update_progress(index, progress);
+    }
+
+    /// Records new progress for a task and the groups it is in.
+    fn update_progress(&mut self, index: usize, progress: f64) {
+        if index >= self.tasks.len() {
+            return;
+        }
+        self.record_progress(index, progress);
+        self.record_timeline(index);
+        self.update_groups(index);
+
+        let overall = self.overall_progress();
+        if self.beep && self.passed_milestone(overall) {
+            ring_bell();
+        }
+        if self.history.len() == MAX_HISTORY {
+            self.history.pop_front();
+        }
+        self.history.push_back((overall * 100.0).round() as u64);
+    }
+
+    /// Brings every group above the task at `index` up to date with it.
+    fn update_groups(&mut self, index: usize) {
+        let mut child = index;
+        while let Some(parent) = self.tasks.get(child).and_then(|task| task.parent) {
+            let progress = self.snap_complete(self.aggregate_progress(parent));
+            // A child going back reopens a finished group.
+            self.tasks[parent].completed &= progress >= 1.0;
+            self.record_progress(parent, progress);
+            child = parent;
+        }
+    }
+
+    /// Rounds `progress` within the completion tolerance of `1.0` up to it.
+    fn snap_complete(&self, progress: f64) -> f64 {
+        if progress >= 1.0 - self.complete_tolerance {
+            1.0
+        } else {
+            progress
+        }
+    }
+
+    /// Records new progress for the one task at `index`, marking it complete
+    /// (and ringing the terminal bell, once) when it reaches `1.0`, or close
+    /// enough to it.
+    fn record_progress(&mut self, index: usize, progress: f64) {
+        let progress = self.snap_complete(progress);
+        let Some(task) = self.tasks.get_mut(index) else {
+            return;
+        };
+        task.set_progress(progress, self.eta_window);
+        if let Some(window) = self.average_window {
+            if task.readings.len() == window {
+                task.readings.pop_front();
+            }
+            task.readings.push_back(task.progress);
+        }
+        if self.ease.is_none() {
+            task.displayed = task.averaged_progress();
+        }
+        if task.progress >= 1.0 && !task.completed {
+            task.completed = true;
+            if self.completed_log.len() == MAX_COMPLETED_LOG {
+                self.completed_log.remove(0);
+            }
+            self.completed_log.push((task.name.clone(), Instant::now()));
+            ring_bell();
+            if self.notify_on_complete {
+                let body = format!("{} is complete", task.name);
+                if let Err(err) = notify::send("Progress Tracker", &body) {
+                    self.set_status(format!("Notification failed: {err}"));
+                }
+            }
+            if let Some(command) = &self.on_complete
+                && let Err(err) =
+                    hook::spawn(command, &self.tasks[index].name, self.hook_failures.clone())
+            {
+                let message = format!("Failed to run '{command}': {err}");
+                self.set_status(message);
+            }
+        }
+    }
+
+    /// Whether `task`'s command failed or it has stalled.
+    fn is_troubled(&self, task: &Task) -> bool {
+        let failed = task.exit_status.is_some() && !task.completed;
+        failed || self.stall_after.is_some_and(|after| task.is_stalled(after))
+    }
+
+    fn troubled_tasks(&self) -> usize {
+        self.tasks
+            .iter()
+            .filter(|task| self.is_troubled(task))
+            .count()
+    }
+
+    /// Raises the alert when more tasks are in trouble than a key press last
+    /// acknowledged, and drops it once none are.
+    fn update_alert(&mut self) {
+        let troubled = self.troubled_tasks();
+        if troubled > self.acknowledged_alerts {
+            self.alert = true;
+        }
+        if troubled == 0 {
+            self.alert = false;
+        }
+        // Recovered tasks fall out, so going back into trouble alerts again.
+        self.acknowledged_alerts = self.acknowledged_alerts.min(troubled);
+    }
+
+    /// Appends the latest progress of the task at `index` to the `--csv`
+    /// file, giving up on it after a failed write.
+    fn record_timeline(&mut self, index: usize) {
+        let Some(timeline) = &mut self.timeline else {
+            return;
+        };
+        let task = &self.tasks[index];
+        if let Err(err) = timeline.record(&task.name, task.progress) {
+            let message = format!("Stopped writing {}: {err}", timeline.path().display());
+            self.timeline = None;
+            self.set_status(message);
+        }
+    }
+
+    /// Appends every task's progress to the `--log-file`, at most once per
+    /// `LOG_INTERVAL` unless `force` is set, and gives up on the file with a
+    /// status message should writing fail.
+    fn log_progress(&mut self, force: bool) {
+        let Some(log) = &mut self.progress_log else {
+            return;
+        };
+        let now = Instant::now();
+        let written = self
+            .tasks
+            .iter()
+            .enumerate()
+            .try_for_each(|(index, task)| log.record(index, &task.name, task.progress, now, force))
+            .and_then(|()| log.flush(now, force));
+        if let Err(err) = written {
+            let message = format!("Stopped logging to {}: {err}", log.path().display());
+            self.progress_log = None;
+            self.set_status(message);
+        }
+    }
+
+    /// Shows the latest failure of an `--on-complete` command, if any
+    /// finished since the last tick.
+    fn report_hook_failures(&mut self) {
+        let failure = match self.hook_failures.lock() {
+            Ok(mut failures) => failures.drain(..).next_back(),
+            Err(_) => None,
+        };
+        if let Some(failure) = failure {
+            self.set_status(failure);
+        }
+    }
+
+    /// Whether `overall` has reached a decile the bell hasn't rung for yet.
+    ///
+    /// Only falling a whole decile back re-arms the lower ones, so progress
+    /// wobbling around a boundary rings once. 100% is left to the completion
+    /// bell.
+    fn passed_milestone(&mut self, overall: f64) -> bool {
+        // Nudged up so that e.g. 0.3 * 10.0 doesn't land just below 3.
+        let decile = ((overall * 10.0 + 1e-9).floor() as usize).min(9);
+        if decile > self.beep_milestone {
+            self.beep_milestone = decile;
+            return true;
+        }
+        if decile + 1 < self.beep_milestone {
+            self.beep_milestone = decile;
+        }
+        false
+    }
+
+    /// In `--duration` mode, fills every task by the share of the duration
+    /// that has passed since it started.
+    fn advance_timers(&mut self) {
+        let Some(duration) = self.duration else {
+            return;
+        };
+        for index in 0..self.tasks.len() {
+            if !self.tasks[index].children.is_empty() {
+                continue;
+            }
+            let elapsed = self.tasks[index].start.elapsed();
+            let progress = (elapsed.as_secs_f64() / duration.as_secs_f64()).min(1.0);
+            self.update_progress(index, progress);
+        }
+    }
+
+    /// Moves each gauge's fill `ease` of the way towards its true progress,
+    /// snapping once it is close enough that further steps wouldn't show.
+    fn ease_displayed_progress(&mut self) {
+        let Some(ease) = self.ease else {
+            return;
+        };
+        for task in &mut self.tasks {
+            let target = task.averaged_progress();
+            let gap = target - task.displayed;
+            task.displayed = if gap.abs() < EASE_SNAP {
+                target
+            } else {
+                task.displayed + gap * ease
+            };
+        }
+    }
+
+    /// Progress across all tasks weighted by `Task::weight`, a plain mean if
+    /// every weight is zero, or `0.0` when there are no tasks.
+    fn overall_progress(&self) -> f64 {
+        let roots: Vec<usize> = (0..self.tasks.len())
+            .filter(|&index| self.tasks[index].parent.is_none())
+            .collect();
+        self.weighted_progress(&roots)
+    }
+
+    /// The progress of the task at `index`: its own, or for a group that of
+    /// the tasks in it.
+    fn aggregate_progress(&self, index: usize) -> f64 {
+        let task = &self.tasks[index];
+        if task.children.is_empty() {
+            task.progress
+        } else {
+            self.weighted_progress(&task.children)
+        }
+    }
+
+    /// The progress of the tasks at `indices`, weighted by `--weight`, or the
+    /// plain mean should every weight be zero.
+    fn weighted_progress(&self, indices: &[usize]) -> f64 {
+        if indices.is_empty() {
+            return 0.0;
+        }
+        let total_weight: f64 = indices.iter().map(|&index| self.tasks[index].weight).sum();
+        if total_weight <= 0.0 {
+            let total: f64 = indices
+                .iter()
+                .map(|&index| self.aggregate_progress(index))
+                .sum();
+            return total / indices.len() as f64;
+        }
+        let weighted: f64 = indices
+            .iter()
+            .map(|&index| self.aggregate_progress(index) * self.tasks[index].weight)
+            .sum();
+        weighted / total_weight
+    }
+
+    /// Indices of the tasks in the order they are listed: each group straight
+    /// before its children, which are left out while it is collapsed. A
+    /// group takes the place of its first task.
+    fn visible_order(&self) -> Vec<usize> {
+        let mut roots: Vec<usize> = (0..self.tasks.len())
+            .filter(|&index| self.tasks[index].parent.is_none())
+            .collect();
+        roots.sort_by_key(|&index| self.first_leaf(index));
+        let mut order = Vec::with_capacity(self.tasks.len());
+        let mut stack: Vec<usize> = roots.into_iter().rev().collect();
+        while let Some(index) = stack.pop() {
+            order.push(index);
+            let task = &self.tasks[index];
+            if !task.collapsed {
+                stack.extend(task.children.iter().rev());
+            }
+        }
+        order
+    }
+
+    /// The lowest index of a task with a source at or under `index`.
+    fn first_leaf(&self, index: usize) -> usize {
+        self.tasks[index]
+            .children
+            .iter()
+            .map(|&child| self.first_leaf(child))
+            .min()
+            .unwrap_or(index)
+    }
+
+    /// Every task under the group at `index`, groups included.
+    fn descendants(&self, index: usize) -> Vec<usize> {
+        let mut found = Vec::new();
+        let mut stack = self.tasks[index].children.clone();
+        while let Some(child) = stack.pop() {
+            found.push(child);
+            stack.extend(&self.tasks[child].children);
+        }
+        found
+    }
+
+    /// How many groups the task at `index` is nested in.
+    fn depth(&self, index: usize) -> usize {
+        std::iter::successors(self.tasks[index].parent, |&parent| {
+            self.tasks[parent].parent
+        })
+        .count()
+    }
+
+    /// Collapses or expands the selected group, or collapses the one around
+    /// the selected task.
+    fn toggle_group(&mut self) {
+        let Some(task) = self.tasks.get(self.selected) else {
+            return;
+        };
+        let index = match task.parent {
+            _ if !task.children.is_empty() => self.selected,
+            Some(parent) => parent,
+            None => {
+                self.set_status(format!("{} isn't in a group", task.name));
+                return;
+            }
+        };
+        if index != self.selected {
+            self.select(index);
+        }
+        let group = &mut self.tasks[index];
+        group.collapsed = !group.collapsed;
+        let verb = if group.collapsed {
+            "Collapsed"
+        } else {
+            "Expanded"
+        };
+        let status = format!("{verb} {}", group.name);
+        self.set_status(status);
+    }
+
+    /// Groups the ungrouped tasks named `members`, ignoring case, under
+    /// `name` as `--group` asks.
+    fn group_named(&mut self, name: &str, members: &[String]) -> Result<usize, String> {
+        let children = members
+            .iter()
+            .map(|member| {
+                let index = self
+                    .tasks
+                    .iter()
+                    .position(|task| task.name.eq_ignore_ascii_case(member))
+                    .ok_or_else(|| format!("no task named '{member}' for --group {name}"))?;
+                match self.tasks[index].parent {
+                    Some(_) => Err(format!("'{member}' can't be in more than one --group")),
+                    None => Ok(index),
+                }
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(self.group(name, &children))
+    }
+
+    fn handle_key(&mut self, key: crossterm::event::KeyEvent) -> io::Result<()> {
+        if key.kind != KeyEventKind::Press {
+            return Ok(());
+        }
+        if self.alert {
+            self.alert = false;
+            self.acknowledged_alerts = self.troubled_tasks();
+            self.set_status("Alert acknowledged");
+        }
+        // Raw mode turns Ctrl-C into a key press, so quit on it directly rather
+        // than leaving no way out but killing the process.
+        if key.modifiers.contains(KeyModifiers::CONTROL) {
+            if key.code == KeyCode::Char('c') {
+                self.exit = true;
+            }
+            return Ok(());
+        }
+        if self.goto_prompt {
+            match key.code {
+                KeyCode::Char(digit @ '0'..='9') if self.input_buffer.len() < 3 => {
+                    self.input_buffer.push(digit);
+                }
+                KeyCode::Backspace => {
+                    self.input_buffer.pop();
+                }
+                KeyCode::Enter => match self.input_buffer.parse::<u8>() {
+                    Ok(percent) if percent <= 100 => self.go_to(f64::from(percent) / 100.0),
+                    _ => self.set_status(format!("Invalid percentage '{}'", self.input_buffer)),
+                },
+                KeyCode::Esc => self.set_status("Go to cancelled"),
+                _ => {}
+            }
+            if matches!(key.code, KeyCode::Enter | KeyCode::Esc) {
+                self.goto_prompt = false;
+                self.input_buffer.clear();
+            }
+            return Ok(());
+        }
+        if self.confirm_quit {
+            match key.code {
+                KeyCode::Char('y') => self.exit = true,
+                KeyCode::Char('n') | KeyCode::Esc => {
+                    self.confirm_quit = false;
+                    self.set_status("Quit cancelled");
+                }
+                _ => {}
+            }
+            return Ok(());
+        }
+        if key.code == KeyCode::Esc && self.show_help {
+            self.show_help = false;
+            self.set_status("Help closed");
+        } else if let Some(action) = self
+            .keymap
+            .action_for(key.code, self.active_tab == Tab::Progress)
+        {
+            self.perform(action);
+        }
+        Ok(())
+    }
+
+    fn handle_mouse(&mut self, mouse: crossterm::event::MouseEvent) {
+        if mouse.kind != MouseEventKind::Down(MouseButton::Left) || self.confirm_quit {
+            return;
+        }
+        let position = Position::new(mouse.column, mouse.row);
+        let clicked = self
+            .buttons
+            .borrow()
+            .iter()
+            .find(|(area, _)| area.contains(position))
+            .map(|(_, action)| *action);
+        if let Some(action) = clicked {
+            self.perform(action);
+        }
+    }
+
+    fn perform(&mut self, action: Action) {
+        if Action::UNDOABLE.contains(&action) {
+            self.push_undo();
+        }
+        match action {
+            Action::SelectNext => self.move_selection(1),
+            Action::SelectPrevious => self.move_selection(-1),
+            Action::PageDown => self.move_page(1),
+            Action::PageUp => self.move_page(-1),
+            Action::ToggleColor | Action::TogglePause | Action::Reset => {
+                self.perform_on_selected(action)
+            }
+            Action::ToggleAutoColor => {
+                self.auto_color = !self.auto_color;
+                self.set_status(if self.auto_color {
+                    "Auto-color on"
+                } else {
+                    "Auto-color off"
+                });
+            }
+            Action::ToggleTheme => {
+                self.theme = self.theme.toggled();
+                self.set_status(format!("Switched to the {} theme", self.theme.name));
+            }
+            Action::ToggleCompact => {
+                self.compact = !self.compact;
+                self.set_status(if self.compact {
+                    "Compact view on"
+                } else {
+                    "Compact view off"
+                });
+            }
+            Action::ToggleInstructions => {
+                self.show_instructions = !self.show_instructions;
+                self.set_status(if self.show_instructions {
+                    "Key hints shown"
+                } else {
+                    "Key hints hidden"
+                });
+            }
+            Action::CycleBorder => {
+                let current = BORDER_STYLES
+                    .iter()
+                    .position(|(_, set)| *set == self.border_set)
+                    .unwrap_or(0);
+                let (name, set) = BORDER_STYLES[(current + 1) % BORDER_STYLES.len()];
+                self.border_set = set;
+                self.set_status(format!("Border style: {name}"));
+            }
+            Action::ToggleSmooth => {
+                self.smooth = !self.smooth;
+                self.set_status(if self.smooth {
+                    "Smooth fill on"
+                } else {
+                    "Smooth fill off"
+                });
+            }
+            Action::Snapshot => self.save_snapshot(),
+            Action::ToggleAccessible => {
+                self.accessible = !self.accessible;
+                self.set_status(if self.accessible {
+                    "Patterned gauges on"
+                } else {
+                    "Patterned gauges off"
+                });
+            }
+            Action::GoTo => self.goto_prompt = !self.tasks.is_empty(),
+            Action::StepForward => self.nudge(MANUAL_STEP),
+            Action::StepBack => self.nudge(-MANUAL_STEP),
+            Action::ToggleGroup => self.toggle_group(),
+            Action::DismissError => {
+                if self.error.take().is_some() {
+                    self.set_status("Error dismissed");
+                }
+            }
+            Action::Undo => self.undo(),
+            Action::TogglePauseAll => self.toggle_pause_all(),
+            Action::SpeedUp => self.change_speed(SPEED_STEP),
+            Action::SlowDown => self.change_speed(-SPEED_STEP),
+            Action::NextTab => self.show_tab(self.active_tab.next()),
+            Action::ProgressTab => self.show_tab(Tab::Progress),
+            Action::LogTab => self.show_tab(Tab::Log),
+            Action::StatsTab => self.show_tab(Tab::Stats),
+            Action::SelectTask(position) => {
+                let order = self.visible_order();
+                // Past the last task lands on it.
+                if let Some(&index) = order.get(usize::from(position)).or(order.last()) {
+                    self.select(index);
+                }
+            }
+            Action::ToggleHelp => {
+                self.show_help = !self.show_help;
+                self.set_status(if self.show_help {
+                    "Help opened"
+                } else {
+                    "Help closed"
+                });
+            }
+            Action::Quit => {
+                self.confirm_quit = true;
+                self.set_status("Quit requested");
+            }
+        }
+    }
+
+    /// Saves the settings an undoable action is about to change.
+    fn push_undo(&mut self) {
+        if self.undo_stack.len() == MAX_UNDO {
+            self.undo_stack.remove(0);
+        }
+        let task = self
+            .tasks
+            .get(self.selected)
+            .map(|task| (self.selected, task.color_index, task.color));
+        self.undo_stack.push(AppSnapshot {
+            task,
+            auto_color: self.auto_color,
+            theme: self.theme,
+            compact: self.compact,
+            border_set: self.border_set,
+            smooth: self.smooth,
+            show_instructions: self.show_instructions,
+            accessible: self.accessible,
+        });
+    }
+
+    /// Puts back the settings from before the last undoable action.
+    fn undo(&mut self) {
+        let Some(snapshot) = self.undo_stack.pop() else {
+            self.set_status("Nothing to undo");
+            return;
+        };
+        if let Some((index, color_index, color)) = snapshot.task
+            && let Some(task) = self.tasks.get_mut(index)
+        {
+            (task.color_index, task.color) = (color_index, color);
+        }
+        self.auto_color = snapshot.auto_color;
+        self.theme = snapshot.theme;
+        self.compact = snapshot.compact;
+        self.border_set = snapshot.border_set;
+        self.smooth = snapshot.smooth;
+        self.show_instructions = snapshot.show_instructions;
+        self.accessible = snapshot.accessible;
+        self.set_status("Undid the last change");
+    }
+
+    /// Applies `action`, one of the per-task actions, to the selected task.
+    /// Pausing or resetting a group does the same to every task in it.
+    fn perform_on_selected(&mut self, action: Action) {
+        let index = self.selected;
+        let Some(task) = self.tasks.get(index) else {
+            return;
+        };
+        if task.children.is_empty() || action == Action::ToggleColor {
+            if let Some(status) = self.perform_on(index, action) {
+                self.set_status(status);
+            }
+            return;
+        }
+        let (name, paused) = (task.name.clone(), !task.paused);
+        for descendant in self.descendants(index) {
+            let task = &mut self.tasks[descendant];
+            if !task.children.is_empty() {
+                match action {
+                    Action::Reset => task.reset(),
+                    _ => task.paused = paused,
+                }
+            } else if action == Action::Reset || task.paused != paused {
+                self.perform_on(descendant, action);
+            }
+        }
+        let status = match action {
+            Action::TogglePause => {
+                self.tasks[index].paused = paused;
+                let verb = if paused { "Paused" } else { "Resumed" };
+                format!("{verb} {name}")
+            }
+            Action::Reset => {
+                self.tasks[index].reset();
+                self.update_groups(index);
+                format!("Reset {name}")
+            }
+            _ => return,
+        };
+        self.set_status(status);
+    }
+
+    /// Pauses every running task, or resumes the ones that paused, so tasks
+    /// already paused on their own stay that way.
+    fn toggle_pause_all(&mut self) {
+        let (tasks, pausing) = match self.paused_all.take() {
+            Some(paused) => (paused, false),
+            None => {
+                let running: Vec<usize> = (0..self.tasks.len())
+                    .filter(|&index| !self.tasks[index].paused)
+                    .collect();
+                self.paused_all = Some(running.clone());
+                (running, true)
+            }
+        };
+        let mut count = 0;
+        for index in tasks {
+            // A task resumed by hand in the meantime is already running.
+            let Some(task) = self.tasks.get(index) else {
+                continue;
+            };
+            if task.paused == pausing {
+                continue;
+            }
+            count += usize::from(task.children.is_empty());
+            self.perform_on(index, Action::TogglePause);
+        }
+        let verb = if pausing { "Paused" } else { "Resumed" };
+        let noun = if count == 1 { "task" } else { "tasks" };
+        self.set_status(format!("{verb} {count} {noun}"));
+    }
+
+    /// Applies a per-task `action` to the task at `index`, returning the
+    /// status message to show.
+    fn perform_on(&mut self, index: usize, action: Action) -> Option<String> {
+        let task = self.tasks.get_mut(index)?;
+        let status = match action {
+            Action::ToggleColor => {
+                task.color = None;
+                task.color_index = (task.color_index + 1) % PALETTE.len();
+                format!(
+                    "{} color changed to {:?}",
+                    task.name, PALETTE[task.color_index]
+                )
+            }
+            Action::TogglePause => {
+                task.paused = !task.paused;
+                // Resuming hands a nudged task back to its source.
+                task.manual = task.manual && task.paused;
+                // Time spent paused shouldn't count towards stalling.
+                task.last_progress_at = Instant::now();
+                self.controls.send(if task.paused {
+                    WorkerCommand::Stop(index)
+                } else {
+                    WorkerCommand::Start(index)
+                });
+                let verb = if task.paused { "Paused" } else { "Resumed" };
+                format!("{verb} {}", task.name)
+            }
+            Action::Reset => {
+                self.controls.send(WorkerCommand::Reset(index));
+                task.reset();
+                let status = format!("Reset {}", task.name);
+                self.update_groups(index);
+                status
+            }
+            _ => return None,
+        };
+        Some(status)
+    }
+
+    /// Moves the selected task straight to `progress`, taking its worker along.
+    fn go_to(&mut self, progress: f64) {
+        let index = self.selected;
+        if self.refuse_group(index) {
+            return;
+        }
+        let Some(task) = self.tasks.get_mut(index) else {
+            return;
+        };
+        // The old samples would make the jump look like a burst of speed.
+        task.samples.clear();
+        task.readings.clear();
+        task.completed = task.completed && progress >= 1.0;
+        // A deliberate jump back isn't the source regressing.
+        task.peak = progress;
+        let name = task.name.clone();
+        self.update_progress(index, progress);
+        let reported = self.tasks[index].reported_progress();
+        self.controls.send(WorkerCommand::JumpTo(index, reported));
+        self.set_status(format!(
+            "Moved {name} to {:.*}%",
+            self.precision,
+            progress * 100.0
+        ));
+    }
+
+    /// Moves the selected task `delta` along by hand, clamped to 0-100%, and
+    /// pauses it so neither its worker nor its source undoes the change.
+    fn nudge(&mut self, delta: f64) {
+        let index = self.selected;
+        if self.refuse_group(index) {
+            return;
+        }
+        let Some(task) = self.tasks.get_mut(index) else {
+            return;
+        };
+        if !task.manual {
+            task.manual = true;
+            task.paused = true;
+            self.controls.send(WorkerCommand::Stop(index));
+        }
+        let progress = (task.progress + delta).clamp(0.0, 1.0);
+        self.go_to(progress);
+    }
+
+    /// Says why a group can't be moved by hand, if the task at `index` is one.
+    fn refuse_group(&mut self, index: usize) -> bool {
+        let Some(task) = self
+            .tasks
+            .get(index)
+            .filter(|task| !task.children.is_empty())
+        else {
+            return false;
+        };
+        self.set_status(format!(
+            "{} follows its tasks; select one of them to move it",
+            task.name
+        ));
+        true
+    }
+
+    /// Moves the selection by `delta` tasks, wrapping at either end.
+    fn move_selection(&mut self, delta: isize) {
+        let order = self.visible_order();
+        if order.is_empty() {
+            return;
+        }
+        let len = order.len() as isize;
+        let position = self.selected_position(&order) as isize;
+        self.select(order[(position + delta).rem_euclid(len) as usize]);
+    }
+
+    /// Moves the selection a screenful of tasks down (or up, for a negative
+    /// `direction`), stopping at either end of the list.
+    fn move_page(&mut self, direction: isize) {
+        let order = self.visible_order();
+        if order.is_empty() {
+            return;
+        }
+        let page = self.visible_tasks.get().max(1) as isize;
+        let last = order.len() as isize - 1;
+        let position = self.selected_position(&order) as isize;
+        self.select(order[(position + direction * page).clamp(0, last) as usize]);
+    }
+
+    /// Where the selected task is in `order`, a list from `visible_order`.
+    fn selected_position(&self, order: &[usize]) -> usize {
+        order
+            .iter()
+            .position(|&index| index == self.selected)
+            .unwrap_or(0)
+    }
+
+    /// Selects the task at `index`, scrolling the list just enough to show it.
+    fn select(&mut self, index: usize) {
+        self.selected = index;
+        let order = self.visible_order();
+        let position = self.selected_position(&order);
+        let visible = self.visible_tasks.get().max(1);
+        if position < self.scroll_offset {
+            self.scroll_offset = position;
+        } else if position >= self.scroll_offset + visible {
+            self.scroll_offset = position + 1 - visible;
+        }
+        self.set_status(format!(
+            "Selected {} ({}/{})",
+            self.tasks[index].name,
+            position + 1,
+            order.len()
+        ));
+    }
+
+    /// Restores same-named tasks from `path` if it was saved by a run of the
+    /// same `command`, keeping the defaults if the file is missing, unreadable
+    /// or from another run.
+    ///
+    /// The demo, with no command, picks up its progress and color. A command
+    /// starts over, with an unfinished last run kept as each task's baseline.
+    fn load_state(&mut self, path: &Path, command: &[String]) {
+        let Some(state) = fs::read_to_string(path)
+            .ok()
+            .and_then(|json| serde_json::from_str::<SavedState>(&json).ok())
+            .filter(|state| state.command == command)
+        else {
+            return;
+        };
+        for saved in state.tasks {
+            let Some(task) = self.tasks.iter_mut().find(|task| task.name == saved.name) else {
+                continue;
+            };
+            if !command.is_empty() {
+                task.baseline =
+                    Some(saved.progress).filter(|progress| (0.0..1.0).contains(progress));
+                continue;
+            }
+            if (0.0..=1.0).contains(&saved.progress) {
+                task.progress = saved.progress;
+                task.displayed = saved.progress;
+                task.peak = saved.progress;
+                task.completed = saved.progress >= 1.0;
+            }
+            if saved.color_index < PALETTE.len() {
+                task.color_index = saved.color_index;
+            }
+        }
+    }
+
+    fn save_state(&self, path: &Path, command: &[String]) -> io::Result<()> {
+        let state = SavedState {
+            command: command.to_vec(),
+            tasks: self
+                .tasks
+                .iter()
+                .map(|task| SavedTask {
+                    name: task.name.clone(),
+                    progress: task.progress,
+                    color_index: task.color_index,
+                })
+                .collect(),
+        };
+        fs::write(path, serde_json::to_string_pretty(&state)?)
+    }
+
+    /// Writes out the rows still buffered for the `--csv` file, if there is
+    /// one.
+    fn flush_timeline(&mut self) -> Option<io::Result<()>> {
+        self.timeline.as_mut().map(Timeline::flush)
+    }
+
+    /// Writes what is on screen to a timestamped text file in the current
+    /// directory, reporting where (or why not) in the status line.
+    fn save_snapshot(&mut self) {
+        let mut buffer = Buffer::empty(self.rendered_area.get());
+        (&*self).render(buffer.area, &mut buffer);
+        let path = format!(
+            "progress-{}.txt",
+            chrono::Local::now().format("%Y%m%d-%H%M%S")
+        );
+        match fs::write(&path, buffer_to_text(&buffer)) {
+            Ok(()) => self.set_status(format!("Saved the screen to {path}")),
+            Err(err) => self.set_status(format!("Failed to save {path}: {err}")),
+        }
+    }
+
+    fn change_speed(&mut self, delta: i32) {
+        self.controls.adjust_speed(delta);
+        self.set_status(format!("Speed set to {:.2}x", self.controls.speed()));
+    }
+
+    /// Replaces the status line, stamping it with the current local time.
+    fn set_status(&mut self, message: impl AsRef<str>) {
+        self.status = format!(
+            "[{}] {}",
+            chrono::Local::now().format("%H:%M:%S"),
+            message.as_ref()
+        );
+        if self.messages.len() == MAX_MESSAGES {
+            self.messages.pop_front();
+        }
+        self.messages.push_back(self.status.clone());
+    }
+
+    /// The first key that triggers `action` on the tab showing, if any.
+    fn key_for(&self, action: Action) -> Option<String> {
+        self.keymap
+            .key_for(action, self.active_tab == Tab::Progress)
+    }
+
+    fn show_tab(&mut self, tab: Tab) {
+        self.active_tab = tab;
+        self.set_status(format!("Showing the {} tab", tab.title().to_lowercase()));
+    }
+
+    fn spinner(&self) -> char {
+        SPINNER[self.spinner_frame % SPINNER.len()]
+    }
+
+    fn task_color(&self, task: &Task) -> Color {
+        task.color.unwrap_or(self.theme.gauge[task.color_index])
+    }
+
+    /// Color of the selected task, or the first palette color without tasks.
+    fn selected_color(&self) -> Color {
+        self.tasks
+            .get(self.selected)
+            .map_or(self.theme.gauge[0], |task| self.task_color(task))
+    }
+
+    fn border_style(&self) -> Style {
+        Style::default().fg(self.theme.border)
+    }
+
+    fn draw(&self, frame: &mut Frame) {
+        frame.render_widget(self, frame.area());
+    }
+}
+
+impl Widget for &ProgressApp {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        self.rendered_area.set(area);
+        if self.compact {
+            self.buttons.borrow_mut().clear();
+            self.draw_compact(area, buf);
+            return;
+        }
+        if area.width < MIN_WIDTH || area.height < MIN_HEIGHT {
+            self.buttons.borrow_mut().clear();
+            self.draw_too_small(area, buf);
+            return;
+        }
+        // Optional rows collapse to nothing when the terminal is too short.
+        let optional_row = |min_height: u16, rows: u16| {
+            let rows = if area.height < min_height { 0 } else { rows };
+            Constraint::Length(rows)
+        };
+        let error_rows = if self.error.is_some() { 3 } else { 0 };
+        let layout = Layout::vertical([
+            optional_row(MIN_HEIGHT_FOR_TITLE, 3),
+            Constraint::Length(error_rows),
+            Constraint::Min(0),
+            optional_row(MIN_HEIGHT_FOR_HISTORY, 3),
+            optional_row(MIN_HEIGHT_FOR_TITLE, 1),
+        ]);
+        let [
+            title_area,
+            error_area,
+            tasks_area,
+            history_area,
+            status_area,
+        ] = layout.areas(area);
+
+        // Groups are summaries, so only the tasks in them are counted.
+        let (completed, total) = self
+            .tasks
+            .iter()
+            .filter(|task| task.children.is_empty())
+            .fold((0, 0), |(completed, total), task| {
+                (completed + usize::from(task.completed), total + 1)
+            });
+        self.draw_title(completed, total, title_area, buf);
+        self.draw_error(error_area, buf);
+        let log_width =
+            if self.completed_log.is_empty() || tasks_area.width < MIN_WIDTH_FOR_LOG + LOG_WIDTH {
+                0
+            } else {
+                LOG_WIDTH
+            };
+        match self.active_tab {
+            Tab::Progress => {
+                let [tasks_area, log_area] =
+                    Layout::horizontal([Constraint::Min(0), Constraint::Length(log_width)])
+                        .areas(tasks_area);
+                self.draw_tasks(tasks_area, buf);
+                self.draw_completed_log(log_area, buf);
+            }
+            Tab::Log => self.draw_messages(tasks_area, buf),
+            Tab::Stats => self.draw_stats(tasks_area, buf),
+        }
+        self.draw_history(history_area, buf);
+        self.draw_status(status_area, buf);
+
+        if self.show_help {
+            self.draw_help(area, buf);
+        }
+    }
+}
+
+impl ProgressApp {
+    /// Asks for a bigger terminal in the middle of `area`, wrapping onto the
+    /// rows below when it is too narrow for the two lines.
+    fn draw_too_small(&self, area: Rect, buf: &mut Buffer) {
+        let lines = vec![
+            Line::from("Terminal too small —"),
+            Line::from(format!("resize to at least {MIN_WIDTH}x{MIN_HEIGHT}")),
+        ];
+        let top = area.height.saturating_sub(lines.len() as u16) / 2;
+        Paragraph::new(lines)
+            .style(Style::default().fg(self.theme.warning))
+            .centered()
+            .wrap(Wrap { trim: true })
+            .render(
+                Rect {
+                    y: area.y + top,
+                    height: area.height - top,
+                    ..area
+                },
+                buf,
+            );
+    }
+
+    /// Draws the whole app as one line on the first row of `area`, for tmux
+    /// status bars and other tight spaces.
+    fn draw_compact(&self, area: Rect, buf: &mut Buffer) {
+        let elapsed = self
+            .tasks
+            .iter()
+            .map(|task| task.start.elapsed())
+            .max()
+            .unwrap_or_default();
+        let mut line = compact_bar(self.overall_progress(), elapsed, self.precision, area.width);
+        if self.confirm_quit {
+            line.push_str(" Quit? (y/n)");
+        }
+        Line::from(line).render(
+            Rect {
+                height: 1.min(area.height),
+                ..area
+            },
+            buf,
+        );
+    }
+
+    /// Draws the heading, naming the task when there is only one and counting
+    /// the `completed` of `total` tasks otherwise, above the overall gauge and
+    /// the tab bar.
+    fn draw_title(&self, completed: usize, total: usize, area: Rect, buf: &mut Buffer) {
+        let [heading_area, overall_area, tabs_area] =
+            Layout::vertical([Constraint::Length(1); 3]).areas(area);
+        let icon = if self.no_emoji { "[*]" } else { "🛠️ " };
+        let context = match self.tasks.as_slice() {
+            [task] => task.name.clone(),
+            _ => format!("{completed}/{total} complete"),
+        };
+        let heading = format!("{icon} Process Overview — {context}");
+        // Equal margins either side keep the heading centered on the screen,
+        // and the clock is dropped before it would crowd the heading.
+        let clock_width = if self.show_clock
+            && heading.width() + 2 * CLOCK_WIDTH <= usize::from(heading_area.width)
+        {
+            CLOCK_WIDTH as u16
+        } else {
+            0
+        };
+        let [_, heading_area, clock_area] = Layout::horizontal([
+            Constraint::Length(clock_width),
+            Constraint::Min(0),
+            Constraint::Length(clock_width),
+        ])
+        .areas(heading_area);
+        let padding = centered_padding(&heading, heading_area.width);
+        Line::styled(
+            heading,
+            Style::default()
+                .fg(self.theme.title)
+                .add_modifier(Modifier::BOLD),
+        )
+        .render(
+            Rect {
+                x: heading_area.x + padding,
+                width: heading_area.width - padding,
+                ..heading_area
+            },
+            buf,
+        );
+        if clock_width > 0 {
+            Line::styled(
+                chrono::Local::now().format("%H:%M:%S ").to_string(),
+                Style::default().fg(self.theme.title),
+            )
+            .right_aligned()
+            .render(clock_area, buf);
+        }
+
+        let overall = self.overall_progress();
+        Gauge::default()
+            .gauge_style(Style::default().fg(self.theme.threshold_color(overall)))
+            .label(Span::styled(
+                format!("Overall {:.*}%", self.precision, overall * 100.0),
+                Style::default().add_modifier(Modifier::BOLD),
+            ))
+            .ratio(overall)
+            .use_unicode(self.smooth)
+            .render(overall_area, buf);
+
+        let selected = Tab::ALL
+            .iter()
+            .position(|tab| *tab == self.active_tab)
+            .unwrap_or(0);
+        Tabs::new(Tab::ALL.map(Tab::title))
+            .select(selected)
+            .highlight_style(
+                Style::default()
+                    .fg(self.theme.accent)
+                    .add_modifier(Modifier::BOLD),
+            )
+            .render(tabs_area, buf);
+    }
+
+    fn draw_tasks(&self, area: Rect, buf: &mut Buffer) {
+        let instructions = if self.confirm_quit {
+            Line::from(Span::styled(
+                " Quit? (y/n) ",
+                Style::default().fg(Color::Red).add_modifier(Modifier::BOLD),
+            ))
+            .centered()
+        } else {
+            let mut spans = vec![Span::styled("Press ", Style::default())];
+            let hints = KEY_HINTS
+                .iter()
+                .filter_map(|(action, text)| Some((self.key_for(*action)?, text)));
+            for (i, (key, action)) in hints.enumerate() {
+                if i > 0 {
+                    spans.push(Span::styled(" | ", Style::default()));
+                }
+                // Single keys are capitalized so they stand out in the footer.
+                let key = if key.chars().count() == 1 {
+                    key.to_uppercase()
+                } else {
+                    key
+                };
+                spans.push(Span::styled(
+                    key,
+                    Style::default()
+                        .fg(self.theme.accent)
+                        .add_modifier(Modifier::BOLD),
+                ));
+                spans.push(Span::styled(format!(" to {action}"), Style::default()));
+            }
+            Line::from(spans).centered()
+        };
+
+        // Without the footer the bottom border goes too, leaving its row to
+        // the gauges. The quit prompt is shown either way.
+        let borders = if self.show_instructions || self.confirm_quit {
+            Borders::ALL
+        } else {
+            Borders::ALL - Borders::BOTTOM
+        };
+        let mut block = Block::new()
+            .borders(borders)
+            .title("Background Processes")
+            .title(
+                Line::from(format!(
+                    " {}Speed {:.2}x ",
+                    if self.paused_all.is_some() {
+                        "All paused • "
+                    } else {
+                        ""
+                    },
+                    self.controls.speed()
+                ))
+                .right_aligned(),
+            )
+            .border_set(self.border_set)
+            .border_style(self.border_style())
+            .style(Style::default());
+        // Drop the footer rather than let it overflow a cramped terminal.
+        if borders.contains(Borders::BOTTOM)
+            && instructions.width() + 2 <= usize::from(area.width)
+            && area.height > GAUGE_HEIGHT
+        {
+            block = block.title_bottom(instructions);
+        }
+        let mut inner = block.inner(area);
+        block.render(area, buf);
+        // The comparison needs a row of its own, as long as both gauges fit.
+        let mut comparison = None;
+        if self.leader().is_some() && inner.height > 2 * GAUGE_HEIGHT {
+            let [gauges, row] =
+                Layout::vertical([Constraint::Min(0), Constraint::Length(1)]).areas(inner);
+            (inner, comparison) = (gauges, Some(row));
+        }
+
+        let order = self.visible_order();
+        let gauge_rows = self.gauge_rows(inner);
+        let visible = if self.vertical {
+            usize::from(inner.width / VERTICAL_GAUGE_WIDTH)
+        } else {
+            usize::from(inner.height / gauge_rows)
+        };
+        self.visible_tasks.set(visible);
+        if visible == 0 {
+            return;
+        }
+        // A resize can leave the stored offset stale, so keep the selection on
+        // screen and avoid blank rows below the last task.
+        let selected = self.selected_position(&order);
+        let offset = self
+            .scroll_offset
+            .min(order.len().saturating_sub(visible))
+            .clamp((selected + 1).saturating_sub(visible), selected);
+        let layout = if self.vertical {
+            Layout::horizontal([Constraint::Length(VERTICAL_GAUGE_WIDTH)].repeat(visible))
+        } else {
+            Layout::vertical([Constraint::Length(gauge_rows)].repeat(visible))
+        };
+        for (&index, task_area) in order.iter().skip(offset).zip(layout.split(inner).iter()) {
+            let mut task_area = *task_area;
+            if !self.vertical {
+                let indent = u16::try_from(self.depth(index))
+                    .unwrap_or(u16::MAX)
+                    .saturating_mul(TREE_INDENT)
+                    .min(task_area.width / 2);
+                task_area.x += indent;
+                task_area.width -= indent;
+            }
+            self.draw_progress_bar(index, task_area, gauge_rows, buf);
+        }
+        // Straight under horizontal gauges, or along the bottom of columns.
+        if let Some(row) = comparison {
+            let y = if self.vertical {
+                row.y
+            } else {
+                row.y.min(inner.y + 2 * gauge_rows)
+            };
+            self.draw_comparison(Rect { y, ..row }, buf);
+        }
+
+        // The scrollbar panics in an area with no width.
+        if order.len() > visible && !inner.is_empty() {
+            let mut state = ScrollbarState::new(order.len() - visible)
+                .position(offset)
+                .viewport_content_length(visible);
+            let (orientation, margin) = if self.vertical {
+                (ScrollbarOrientation::HorizontalBottom, Margin::new(1, 0))
+            } else {
+                (ScrollbarOrientation::VerticalRight, Margin::new(0, 1))
+            };
+            Scrollbar::new(orientation).render(area.inner(margin), buf, &mut state);
+        }
+    }
+
+    /// Rows each horizontal gauge takes in `inner`: an even share of it, so
+    /// every task fits if it can, between `GAUGE_HEIGHT` and `--gauge-height`.
+    fn gauge_rows(&self, inner: Rect) -> u16 {
+        let tallest = self.gauge_height.max(GAUGE_HEIGHT);
+        let tasks = u16::try_from(self.visible_order().len().max(1)).unwrap_or(u16::MAX);
+        (inner.height / tasks).clamp(GAUGE_HEIGHT, tallest)
+    }
+
+    /// With `--compare`, the task ahead of the other and by how much, ties
+    /// going to the first.
+    fn leader(&self) -> Option<(usize, f64)> {
+        let [first, second] = self.tasks.as_slice() else {
+            return None;
+        };
+        let lead = first.progress - second.progress;
+        self.compare
+            .then_some((usize::from(lead < 0.0), lead.abs()))
+    }
+
+    /// Draws which of the two compared tasks is ahead and by how much, timed
+    /// from whichever started first so both share one clock.
+    fn draw_comparison(&self, area: Rect, buf: &mut Buffer) {
+        let Some((leader, lead)) = self.leader() else {
+            return;
+        };
+        let elapsed = self
+            .tasks
+            .iter()
+            .map(|task| task.start.elapsed())
+            .max()
+            .unwrap_or_default();
+        let lead_text = format!("{:.*}", self.precision, lead * 100.0);
+        let mut line = if lead_text.trim_start_matches(['0', '.']).is_empty() {
+            Line::from(format!(
+                "Level at {:.*}%",
+                self.precision,
+                self.tasks[0].progress * 100.0
+            ))
+        } else {
+            Line::from(vec![
+                Span::styled(
+                    self.tasks[leader].name.as_str(),
+                    Style::default()
+                        .fg(self.theme.accent)
+                        .add_modifier(Modifier::BOLD),
+                ),
+                Span::raw(format!(" ahead by {lead_text}%")),
+            ])
+        };
+        line.push_span(Span::raw(format!(" after {:.1}s", elapsed.as_secs_f64())));
+        line.centered().render(area, buf);
+    }
+
+    /// Draws the gauge of the task at `index` at the top of `area`, `rows`
+    /// tall unless it is laid out vertically.
+    fn draw_progress_bar(&self, index: usize, area: Rect, rows: u16, buf: &mut Buffer) {
+        let (task, selected) = (&self.tasks[index], index == self.selected);
+        let area = if self.vertical {
+            Rect {
+                width: area.width.min(VERTICAL_GAUGE_WIDTH),
+                ..area
+            }
+        } else {
+            Rect {
+                height: area.height.min(rows),
+                ..area
+            }
+        };
+        // Indeterminate tasks already spin in their label.
+        let running = !task.completed && !task.paused && task.exit_status.is_none();
+        let name = match task.children.len() {
+            0 => task.name.clone(),
+            _ if !task.collapsed => format!("▾ {}", task.name),
+            1 => format!("▸ {} (1 task)", task.name),
+            tasks => format!("▸ {} ({tasks} tasks)", task.name),
+        };
+        let mut title = Line::from(if running && !task.is_indeterminate() {
+            format!("{} {name}", self.spinner())
+        } else {
+            name
+        });
+        if let Some(phase) = task.active_phase().filter(|_| !task.completed) {
+            title.push_span(Span::styled(
+                format!(
+                    " ▸ {} ({}/{})",
+                    phase.name,
+                    task.phase + 1,
+                    task.phases.len()
+                ),
+                Style::default().add_modifier(Modifier::BOLD),
+            ));
+        }
+        let mut block = Block::bordered()
+            .title(title)
+            .border_set(self.border_set)
+            .border_style(self.border_style())
+            .style(Style::default());
+        if selected {
+            block = block
+                .border_set(border::DOUBLE)
+                .border_style(Style::default().fg(self.theme.accent));
+        }
+        let stalled = self.stall_after.is_some_and(|after| task.is_stalled(after));
+        // Unacknowledged trouble blinks, flipping the border on every tick.
+        let blink_off = self.alert && self.spinner_frame % 2 == 1;
+        if self.is_troubled(task) && !blink_off {
+            let color = if stalled {
+                self.theme.warning
+            } else {
+                Color::Red
+            };
+            block = block.border_style(Style::default().fg(color));
+        }
+
+        let color = if self.auto_color {
+            self.theme.threshold_color(task.progress)
+        } else {
+            self.task_color(task)
+        };
+        let mut gauge_style = Style::default().fg(color);
+        let mut label = if task.is_indeterminate() {
+            let done = match task.bytes {
+                Some((current, _)) => format!("{} so far", label::human_bytes(current)),
+                None => "Working…".to_string(),
+            };
+            format!(
+                "{} {done} ({:.1}s)",
+                self.spinner(),
+                task.start.elapsed().as_secs_f64()
+            )
+        } else if let Some(duration) = self.duration {
+            let left = duration.saturating_sub(task.start.elapsed());
+            format!(
+                "{:.*}% • {:.0}s left",
+                self.precision,
+                task.progress * 100.0,
+                left.as_secs_f64().ceil()
+            )
+        } else {
+            self.label_format.render(&LabelValues {
+                ratio: task.progress,
+                elapsed: task.start.elapsed(),
+                rate: task.rate(),
+                eta: task.eta(),
+                count: task.count,
+                bytes: task.bytes,
+                phase: task
+                    .active_phase()
+                    .map(|phase| (phase.name.clone(), phase.progress)),
+                precision: self.precision,
+            })
+        };
+        match task.exit_status {
+            _ if task.completed => {
+                gauge_style = gauge_style.fg(self.theme.complete);
+                label = "✓ Complete".to_string();
+            }
+            Some(status) => {
+                gauge_style = gauge_style.fg(Color::Red);
+                label = format!(
+                    "{:.*}% • ✗ Command failed ({status})",
+                    self.precision,
+                    task.progress * 100.0
+                );
+            }
+            None if task.paused => {
+                gauge_style = gauge_style.add_modifier(Modifier::DIM);
+                label.push_str(if task.manual {
+                    " (manual)"
+                } else {
+                    " (paused)"
+                });
+            }
+            None if stalled => label.push_str(" ⚠ stalled"),
+            None => {}
+        }
+
+        // The change over the last tick, colored once the bar is drawn.
+        let mut delta = None;
+        if running && !task.is_indeterminate() && task.tick_delta.abs() >= 0.0005 {
+            let color = if task.tick_delta > 0.0 {
+                self.theme.gauge[0]
+            } else {
+                self.theme.gauge[4]
+            };
+            let text = format!("({:+.1}%)", task.tick_delta * 100.0);
+            label.push(' ');
+            delta = Some((Span::raw(&label).width(), text.len(), color));
+            label.push_str(&text);
+        }
+        let label = Span::styled(label, Style::default().add_modifier(Modifier::BOLD));
+        let label_width = label.width();
+        let inner = block.inner(area);
+        let (bar, label_area) = split_label(inner, label_width, self.label_align);
+        if task.is_indeterminate() && !task.completed && task.exit_status.is_none() {
+            block.render(area, buf);
+            buf.set_style(marquee(bar, self.spinner_frame), gauge_style.reversed());
+            buf.set_span(label_area.x, label_area.y, &label, label_area.width);
+            return;
+        }
+
+        let eased = |ratio: f64| ratio.powf(self.curve);
+        if self.vertical {
+            // Columns are too narrow for the full label.
+            let label = match task.exit_status {
+                _ if task.completed => "✓".to_string(),
+                Some(_) => "✗".to_string(),
+                None => format!("{:.*}%", self.precision, task.progress * 100.0),
+            };
+            block.render(area, buf);
+            draw_vertical_bar(
+                eased(task.displayed),
+                gauge_style,
+                &label,
+                self.smooth,
+                inner,
+                buf,
+            );
+            return;
+        }
+        block.render(area, buf);
+        let ratio = eased(task.displayed);
+        if self.accessible {
+            draw_pattern_bar(ratio, gauge_style, label, label_area, bar, buf);
+        } else if self.gradient && !task.completed && task.exit_status.is_none() {
+            let modifier = gauge_style.add_modifier;
+            draw_gradient_bar(ratio, modifier, label, label_area, self.smooth, bar, buf);
+        } else {
+            draw_solid_bar(ratio, gauge_style, label, label_area, self.smooth, bar, buf);
+        }
+        let leads = self
+            .leader()
+            .is_some_and(|(leader, lead)| leader == index && lead > 0.0);
+        if leads {
+            let width = usize::from(label_area.width);
+            color_label_part(label_area, 0..width, self.theme.accent, buf);
+        }
+        // A label cut to fit may have lost the delta.
+        if let Some((offset, width, color)) = delta
+            && usize::from(label_area.width) == label_width
+        {
+            color_label_part(label_area, offset..offset + width, color, buf);
+        }
+        if let Some(baseline) = task.baseline {
+            draw_baseline(ratio, eased(baseline), gauge_style, bar, label_area, buf);
+        }
+        if task.peak > task.progress {
+            draw_peak_marker(eased(task.peak), gauge_style, bar, buf);
+        }
+        // Milestones go on the gauge's bottom border, while it has one.
+        if inner.bottom() < area.bottom() {
+            let row = Rect {
+                y: inner.bottom(),
+                height: 1,
+                ..bar
+            };
+            for &milestone in &self.milestones {
+                let passed = task.progress >= milestone;
+                draw_milestone(eased(milestone), passed, gauge_style, row, buf);
+            }
+        }
+    }
+
+    fn draw_completed_log(&self, area: Rect, buf: &mut Buffer) {
+        if area.is_empty() {
+            return;
+        }
+        let items = self.completed_log.iter().rev().map(|(name, at)| {
+            ListItem::new(Line::from(vec![
+                Span::styled("✓ ", Style::default().fg(self.theme.complete)),
+                Span::raw(name.as_str()),
+                Span::styled(
+                    format!(" {}s ago", at.elapsed().as_secs()),
+                    Style::default().add_modifier(Modifier::DIM),
+                ),
+            ]))
+        });
+        let list = List::new(items).block(
+            Block::bordered()
+                .title("Completed")
+                .border_set(self.border_set)
+                .border_style(self.border_style()),
+        );
+        Widget::render(list, area, buf);
+    }
+
+    /// Lists the status messages, newest first.
+    fn draw_messages(&self, area: Rect, buf: &mut Buffer) {
+        let items = self
+            .messages
+            .iter()
+            .rev()
+            .map(|message| ListItem::new(message.as_str()));
+        let list = List::new(items).block(
+            Block::bordered()
+                .title("Log")
+                .border_set(self.border_set)
+                .border_style(self.border_style()),
+        );
+        Widget::render(list, area, buf);
+    }
+
+    /// Shows the selected task's figures as a key/value list.
+    fn draw_stats(&self, area: Rect, buf: &mut Buffer) {
+        let block = Block::bordered()
+            .title("Stats")
+            .border_set(self.border_set)
+            .border_style(self.border_style());
+        let Some(task) = self.tasks.get(self.selected) else {
+            block.render(area, buf);
+            return;
+        };
+        let percent = |ratio: f64| format!("{:.*}%", self.precision, ratio * 100.0);
+        let state = match task.exit_status {
+            _ if task.completed => "complete".to_string(),
+            Some(status) => format!("failed ({status})"),
+            None if task.manual => "manual".to_string(),
+            None if task.paused => "paused".to_string(),
+            None if self.stall_after.is_some_and(|after| task.is_stalled(after)) => {
+                "stalled".to_string()
+            }
+            None => "running".to_string(),
+        };
+        let mut stats = vec![
+            ("Task", task.name.clone()),
+            ("State", state),
+            ("Progress", percent(task.progress)),
+            ("Peak", percent(task.peak)),
+            (
+                "Elapsed",
+                format!("{:.1}s", task.start.elapsed().as_secs_f64()),
+            ),
+            ("Rate", format!("{:.1}%/s", task.rate() * 100.0)),
+            (
+                "ETA",
+                task.eta().map_or("--".to_string(), |left| {
+                    format!("~{:.0}s left", left.as_secs_f64())
+                }),
+            ),
+            ("Weight", format!("{}", task.weight)),
+        ];
+        if let Some((done, total)) = task.count {
+            stats.push(("Count", format!("{done}/{total}")));
+        }
+        if let Some(phase) = task.active_phase() {
+            stats.push((
+                "Phase",
+                format!("{} ({}/{})", phase.name, task.phase + 1, task.phases.len()),
+            ));
+        }
+        let key_width = stats.iter().map(|(key, _)| key.len()).max().unwrap_or(0);
+        let lines: Vec<Line> = stats
+            .into_iter()
+            .map(|(key, value)| {
+                Line::from(vec![
+                    Span::styled(
+                        format!("{key:<key_width$}  "),
+                        Style::default()
+                            .fg(self.theme.accent)
+                            .add_modifier(Modifier::BOLD),
+                    ),
+                    Span::raw(value),
+                ])
+            })
+            .collect();
+        Paragraph::new(lines).block(block).render(area, buf);
+    }
+
+    fn draw_history(&self, area: Rect, buf: &mut Buffer) {
+        let block = Block::bordered()
+            .title("History")
+            .border_set(self.border_set)
+            .border_style(self.border_style());
+        let width = usize::from(block.inner(area).width);
+        let skip = self.history.len().saturating_sub(width);
+        let data: Vec<u64> = self.history.iter().skip(skip).copied().collect();
+
+        Sparkline::default()
+            .block(block)
+            .data(&data)
+            .max(100)
+            .style(Style::default().fg(self.selected_color()))
+            .render(area, buf);
+    }
+
+    fn draw_status(&self, area: Rect, buf: &mut Buffer) {
+        let mut buttons = self.buttons.borrow_mut();
+        buttons.clear();
+
+        // Buttons sit on the right, each followed by a one-column gap.
+        let buttons_width = Action::BUTTONS
+            .iter()
+            .map(|(_, label)| label.len() as u16 + 1)
+            .sum();
+        let [status_area, buttons_area] =
+            Layout::horizontal([Constraint::Min(0), Constraint::Length(buttons_width)]).areas(area);
+
+        if self.goto_prompt {
+            Line::from(vec![
+                Span::styled(
+                    "Go to (0-100, Enter/Esc): ",
+                    Style::default().fg(self.theme.accent),
+                ),
+                Span::raw(format!("{}%", self.input_buffer)),
+            ])
+            .render(status_area, buf);
+        } else {
+            Line::from(Span::styled(
+                self.status.as_str(),
+                Style::default().add_modifier(Modifier::ITALIC),
+            ))
+            .render(status_area, buf);
+        }
+
+        let mut x = buttons_area.x;
+        for (action, label) in Action::BUTTONS {
+            let button = Rect::new(x, buttons_area.y, label.len() as u16, buttons_area.height)
+                .intersection(buttons_area);
+            if button.is_empty() {
+                break;
+            }
+            Span::styled(
+                label,
+                Style::default()
+                    .fg(self.theme.accent)
+                    .add_modifier(Modifier::REVERSED),
+            )
+            .render(button, buf);
+            buttons.push((button, action));
+            x += label.len() as u16 + 1;
+        }
+    }
+
+    /// Draws the latest source failure in a red box, with the key that
+    /// dismisses it in the corner.
+    fn draw_error(&self, area: Rect, buf: &mut Buffer) {
+        let Some(error) = &self.error else {
+            return;
+        };
+        let red = self.theme.gauge[4];
+        let mut block = Block::bordered()
+            .border_set(self.border_set)
+            .border_style(Style::default().fg(red))
+            .title(Span::styled(
+                " Error ",
+                Style::default().fg(red).add_modifier(Modifier::BOLD),
+            ));
+        if let Some(key) = self.key_for(Action::DismissError) {
+            block = block.title_bottom(Line::from(format!(" {key} to dismiss ")).right_aligned());
+        }
+        Paragraph::new(error.as_str())
+            .style(Style::default().fg(red).add_modifier(Modifier::BOLD))
+            .block(block)
+            .render(area, buf);
+    }
+
+    fn draw_help(&self, area: Rect, buf: &mut Buffer) {
+        buf.set_style(area, Style::default().add_modifier(Modifier::DIM));
+
+        // Tall enough for every binding, as far as the terminal allows.
+        let popup = centered_rect(60, 100, area);
+        // Only the keys that work on this tab, leaving out rows with none.
+        let bindings: Vec<(String, &str)> = KEY_BINDINGS
+            .iter()
+            .filter_map(|(actions, text)| {
+                let keys: Vec<String> = actions
+                    .iter()
+                    .filter_map(|action| self.key_for(*action))
+                    .collect();
+                // The task jumps show their first and last key as a range.
+                let separator = if actions[0].needs_gauges() { "-" } else { "/" };
+                (!keys.is_empty()).then(|| (keys.join(separator), *text))
+            })
+            .chain([("Ctrl-C".to_string(), "Quit immediately")])
+            .collect();
+        let height = (bindings.len() as u16 + 2).min(popup.height);
+        let popup = Rect {
+            y: popup.y + (popup.height - height) / 2,
+            height,
+            ..popup
+        };
+        let key_width = bindings.iter().map(|(key, _)| key.len()).max().unwrap_or(0);
+        let lines: Vec<Line> = bindings
+            .iter()
+            .map(|(key, action)| {
+                Line::from(vec![
+                    Span::styled(
+                        format!("{key:>key_width$}  "),
+                        Style::default()
+                            .fg(self.theme.accent)
+                            .add_modifier(Modifier::BOLD),
+                    ),
+                    Span::styled(*action, Style::default()),
+                ])
+            })
+            .collect();
+
+        Clear.render(popup, buf);
+        Paragraph::new(lines)
+            .block(
+                Block::bordered()
+                    .title(" Help ")
+                    .border_style(self.border_style())
+                    .title_bottom(Line::from(" ? or Esc to close ").centered())
+                    .border_set(self.border_set),
+            )
+            .render(popup, buf);
+    }
+}
+
+/// Rings the terminal bell, unless stdout is redirected, e.g. by `--oneshot`,
+/// where the bell would only end up in the output.
+fn ring_bell() {
+    let mut stdout = io::stdout();
+    if !stdout.is_terminal() {
+        return;
+    }
+    let _ = stdout.write_all(b"\x07");
+    let _ = stdout.flush();
+}
+
+/// Fills `area` with `▓` up to `ratio` and `░` after it, so the bar reads
+/// without color, and draws `label` at `label_area` in the terminal's own
+/// colors.
+fn draw_pattern_bar(
+    ratio: f64,
+    style: Style,
+    label: Span,
+    label_area: Rect,
+    area: Rect,
+    buf: &mut Buffer,
+) {
+    let filled = (f64::from(area.width) * ratio.clamp(0.0, 1.0)).round() as u16;
+    for y in area.top()..area.bottom() {
+        for x in area.left()..area.right() {
+            let symbol = if x < area.left() + filled {
+                "▓"
+            } else {
+                "░"
+            };
+            buf[(x, y)].set_symbol(symbol).set_style(style);
+        }
+    }
+    if label_area.is_empty() {
+        return;
+    }
+    // A blank cell either side, where the bar has room, sets the label apart
+    // from the pattern.
+    let style = label.style.fg(Color::Reset).bg(Color::Reset);
+    let padded = Rect {
+        x: label_area.x.saturating_sub(1),
+        width: label_area.width + 2,
+        ..label_area
+    }
+    .intersection(area);
+    for x in padded.left()..padded.right() {
+        buf[(x, padded.y)].set_symbol(" ").set_style(style);
+    }
+    let label = Span::styled(label.content, style);
+    buf.set_span(label_area.x, label_area.y, &label, label_area.width);
+}
+
+/// Fills `area` up to `ratio` in `style` as Ratatui's `Gauge` does, but with
+/// `label` drawn at `label_area`, which may lie beside the bar. Under the
+/// label the colors swap so the text reads over the fill.
+fn draw_solid_bar(
+    ratio: f64,
+    style: Style,
+    label: Span,
+    label_area: Rect,
+    smooth: bool,
+    area: Rect,
+    buf: &mut Buffer,
+) {
+    if area.is_empty() {
+        return;
+    }
+    buf.set_style(area, style);
+    let (fg, bg) = (
+        style.fg.unwrap_or(Color::Reset),
+        style.bg.unwrap_or(Color::Reset),
+    );
+    let filled = f64::from(area.width) * ratio.clamp(0.0, 1.0);
+    let end = area.left()
+        + if smooth {
+            filled.floor()
+        } else {
+            filled.round()
+        } as u16;
+    for y in area.top()..area.bottom() {
+        for x in area.left()..end {
+            let cell = &mut buf[(x, y)];
+            if label_area.contains(Position { x, y }) {
+                cell.set_symbol(" ").set_fg(bg).set_bg(fg);
+            } else {
+                cell.set_symbol(symbols::block::FULL).set_fg(fg).set_bg(bg);
+            }
+        }
+        if smooth && end < area.right() {
+            let symbol = match ((filled % 1.0) * 8.0).round() as usize {
+                0 => " ",
+                8 => symbols::block::FULL,
+                eighths => HORIZONTAL_EIGHTHS[eighths - 1],
+            };
+            buf[(end, y)].set_symbol(symbol);
+        }
+    }
+    if !label_area.is_empty() {
+        buf.set_span(label_area.x, label_area.y, &label, label_area.width);
+    }
+}
+
+/// Columns to leave before `text` to center it in `width`, counted in
+/// terminal cells rather than chars: the `🛠️` icon is two code points drawn
+/// two cells wide.
+fn centered_padding(text: &str, width: u16) -> u16 {
+    let text_width = u16::try_from(text.width()).unwrap_or(u16::MAX);
+    width.saturating_sub(text_width) / 2
+}
+
+/// Splits a horizontal gauge's `area` into its bar and where a label
+/// `label_width` wide lands, on the middle row and cut to fit. Inside the bar
+/// the label keeps a cell off either end when it can; beside it, the label
+/// gives way before the bar drops under half the width.
+fn split_label(area: Rect, label_width: usize, align: LabelAlign) -> (Rect, Rect) {
+    let fit = |room: u16| u16::try_from(label_width).map_or(room, |width| width.min(room));
+    let (y, height) = (area.top() + area.height / 2, u16::from(!area.is_empty()));
+    if align == LabelAlign::Outside {
+        // A one-cell gap separates the label from the bar.
+        let width = fit((area.width / 2).saturating_sub(1));
+        let bar = Rect {
+            width: area.width - width - u16::from(width > 0),
+            ..area
+        };
+        let label = Rect::new(area.right() - width, y, width, height);
+        return (bar, label);
+    }
+    let width = fit(area.width);
+    let margin = u16::from(width + 2 <= area.width);
+    let x = match align {
+        LabelAlign::Left => area.left() + margin,
+        LabelAlign::Right => area.right() - width - margin,
+        _ => area.left() + (area.width - width) / 2,
+    };
+    (area, Rect::new(x, y, width, height))
+}
+
+/// Recolors the `part` of the label drawn at `label`, on the terminal's own
+/// background so the color reads over the fill too.
+fn color_label_part(label: Rect, part: std::ops::Range<usize>, color: Color, buf: &mut Buffer) {
+    for x in part {
+        buf[(label.x + x as u16, label.y)]
+            .set_fg(color)
+            .set_bg(Color::Reset);
+    }
+}
+
+/// Fills `area` up to `ratio` with `GRADIENT`, each cell colored by how far
+/// across the whole bar it sits, so the leading edge shows how far along the
+/// task is. `label` goes at `label_area`, in black or white depending on the
+/// color behind each letter.
+fn draw_gradient_bar(
+    ratio: f64,
+    modifier: Modifier,
+    label: Span,
+    label_area: Rect,
+    smooth: bool,
+    area: Rect,
+    buf: &mut Buffer,
+) {
+    if area.is_empty() {
+        return;
+    }
+    let eighths = (f64::from(area.width) * 8.0 * ratio.clamp(0.0, 1.0)).round() as u16;
+    let eighths = if smooth {
+        eighths
+    } else {
+        (eighths + 4) / 8 * 8
+    };
+    let full = eighths / 8;
+    for column in 0..area.width {
+        let color = gradient_color((f64::from(column) + 0.5) / f64::from(area.width));
+        let x = area.left() + column;
+        for y in area.top()..area.bottom() {
+            let cell = &mut buf[(x, y)];
+            cell.set_style(Style::default().add_modifier(modifier));
+            match eighths.saturating_sub(column * 8).min(8) {
+                0 => {}
+                8 => {
+                    cell.set_char(' ').set_bg(color);
+                }
+                partial => {
+                    cell.set_symbol(HORIZONTAL_EIGHTHS[usize::from(partial) - 1])
+                        .set_fg(color);
+                }
+            }
+        }
+    }
+
+    if label_area.is_empty() {
+        return;
+    }
+    let y = label_area.y;
+    buf.set_span(label_area.x, y, &label, label_area.width);
+    for x in label_area.left()..label_area.right() {
+        let cell = &mut buf[(x, y)];
+        let fg = if x < area.left() + full {
+            readable_on(cell.bg)
+        } else {
+            Color::Reset
+        };
+        cell.set_fg(fg);
+    }
+}
+
+/// The `GRADIENT` color `position` of the way along it, from 0 to 1.
+fn gradient_color(position: f64) -> Color {
+    let scaled = position.clamp(0.0, 1.0) * (GRADIENT.len() - 1) as f64;
+    let stop = (scaled as usize).min(GRADIENT.len() - 2);
+    let along = scaled - stop as f64;
+    let ((r1, g1, b1), (r2, g2, b2)) = (GRADIENT[stop], GRADIENT[stop + 1]);
+    let blend = |from: u8, to: u8| {
+        (f64::from(from) + (f64::from(to) - f64::from(from)) * along).round() as u8
+    };
+    Color::Rgb(blend(r1, r2), blend(g1, g2), blend(b1, b2))
+}
+
+/// Black or white, whichever stands out more against `background`.
+fn readable_on(background: Color) -> Color {
+    let Color::Rgb(r, g, b) = background else {
+        return Color::Reset;
+    };
+    let luma = 0.299 * f64::from(r) + 0.587 * f64::from(g) + 0.114 * f64::from(b);
+    if luma > 128.0 {
+        Color::Black
+    } else {
+        Color::White
+    }
+}
+
+/// Fills `area` from the bottom up to `ratio` of its height, below a top row
+/// holding `label`. With `smooth`, the topmost cell is filled in eighths.
+fn draw_vertical_bar(
+    ratio: f64,
+    style: Style,
+    label: &str,
+    smooth: bool,
+    area: Rect,
+    buf: &mut Buffer,
+) {
+    if area.is_empty() {
+        return;
+    }
+    let label_area = Rect { height: 1, ..area };
+    Line::from(label)
+        .style(Style::default().add_modifier(Modifier::BOLD))
+        .centered()
+        .render(label_area, buf);
+
+    let rows = area.height - 1;
+    let eighths = (f64::from(rows) * 8.0 * ratio.clamp(0.0, 1.0)).round() as u16;
+    let eighths = if smooth {
+        eighths
+    } else {
+        (eighths + 4) / 8 * 8
+    };
+    for row in 0..rows {
+        let symbol = match eighths.saturating_sub(row * 8) {
+            0 => continue,
+            8.. => symbols::bar::FULL,
+            partial => VERTICAL_EIGHTHS[usize::from(partial) - 1],
+        };
+        let y = area.bottom() - 1 - row;
+        for x in area.left()..area.right() {
+            buf[(x, y)].set_symbol(symbol).set_style(style);
+        }
+    }
+}
+
+/// Marks the column `ratio` of the way across `area` with a dim line,
+/// skipping any cell the label is drawn in.
+fn draw_peak_marker(ratio: f64, style: Style, area: Rect, buf: &mut Buffer) {
+    if area.is_empty() {
+        return;
+    }
+    let offset = (f64::from(area.width) * ratio.clamp(0.0, 1.0)).round() as u16;
+    let x = area.left() + offset.min(area.width - 1);
+    for y in area.top()..area.bottom() {
+        let cell = &mut buf[(x, y)];
+        if matches!(cell.symbol(), " " | "░") {
+            cell.set_symbol("│")
+                .set_style(style.add_modifier(Modifier::DIM));
+        }
+    }
+}
+
+/// Shades the empty cells of the bar in `area` between the live fill at
+/// `ratio` and the previous run's `baseline`, around the `label`.
+fn draw_baseline(
+    ratio: f64,
+    baseline: f64,
+    style: Style,
+    area: Rect,
+    label: Rect,
+    buf: &mut Buffer,
+) {
+    let width = f64::from(area.width);
+    let start = (width * ratio.clamp(0.0, 1.0)).ceil() as u16;
+    let end = (width * baseline.clamp(0.0, 1.0)).round() as u16;
+    for x in area.left() + start..area.left() + end {
+        for y in area.top()..area.bottom() {
+            let cell = &mut buf[(x, y)];
+            if cell.symbol() == " " && !label.contains(Position { x, y }) {
+                cell.set_symbol("▒")
+                    .set_style(style.add_modifier(Modifier::DIM));
+            }
+        }
+    }
+}
+
+/// Marks a milestone at `ratio` along the one-row `area`, by the same sums as
+/// the peak marker so the fill reaches it as the task does. Marks at 0% and
+/// 100% sit on the first and last cells. A `passed` milestone is drawn in
+/// the gauge color, the rest dimmed.
+fn draw_milestone(ratio: f64, passed: bool, style: Style, area: Rect, buf: &mut Buffer) {
+    if area.is_empty() {
+        return;
+    }
+    let offset = (f64::from(area.width) * ratio.clamp(0.0, 1.0)).round() as u16;
+    let x = area.left() + offset.min(area.width - 1);
+    let (symbol, style) = if passed {
+        ("▲", style.add_modifier(Modifier::BOLD))
+    } else {
+        ("△", Style::default().add_modifier(Modifier::DIM))
+    };
+    buf[(x, area.top())].set_symbol(symbol).set_style(style);
+}
+
+/// The text of every row of `buffer`, without styles or trailing spaces.
+fn buffer_to_text(buffer: &Buffer) -> String {
+    let area = buffer.area;
+    let mut text = String::new();
+    for y in area.top()..area.bottom() {
+        let mut line = String::new();
+        let mut x = area.left();
+        while x < area.right() {
+            let symbol = buffer[(x, y)].symbol();
+            line.push_str(symbol);
+            // A wide character covers the cells after it too.
+            x += Span::raw(symbol).width().max(1) as u16;
+        }
+        text.push_str(line.trim_end());
+        text.push('\n');
+    }
+    text
+}
+
+/// Like [`buffer_to_text`], but keeping each cell's colors and modifiers as
+/// ANSI escapes, so the frame can be printed outside the UI.
+fn buffer_to_ansi(buffer: &Buffer) -> String {
+    use crossterm::style::{Attribute, SetAttribute, SetBackgroundColor, SetForegroundColor};
+
+    const ATTRIBUTES: [(Modifier, Attribute); 9] = [
+        (Modifier::BOLD, Attribute::Bold),
+        (Modifier::DIM, Attribute::Dim),
+        (Modifier::ITALIC, Attribute::Italic),
+        (Modifier::UNDERLINED, Attribute::Underlined),
+        (Modifier::SLOW_BLINK, Attribute::SlowBlink),
+        (Modifier::RAPID_BLINK, Attribute::RapidBlink),
+        (Modifier::REVERSED, Attribute::Reverse),
+        (Modifier::HIDDEN, Attribute::Hidden),
+        (Modifier::CROSSED_OUT, Attribute::CrossedOut),
+    ];
+    let plain = (Color::Reset, Color::Reset, Modifier::empty());
+    let area = buffer.area;
+    let mut text = String::new();
+    for y in area.top()..area.bottom() {
+        // Trailing blanks are dropped, as long as nothing paints them.
+        let end = (area.left()..area.right())
+            .rev()
+            .find(|&x| {
+                let cell = &buffer[(x, y)];
+                cell.symbol() != " " || cell.bg != Color::Reset || !cell.modifier.is_empty()
+            })
+            .map_or(area.left(), |x| x + 1);
+        let mut current = plain;
+        let mut x = area.left();
+        while x < end {
+            let cell = &buffer[(x, y)];
+            let style = (cell.fg, cell.bg, cell.modifier);
+            if style != current {
+                text.push_str(&SetAttribute(Attribute::Reset).to_string());
+                if cell.fg != Color::Reset {
+                    text.push_str(&SetForegroundColor(cell.fg.into()).to_string());
+                }
+                if cell.bg != Color::Reset {
+                    text.push_str(&SetBackgroundColor(cell.bg.into()).to_string());
+                }
+                for (modifier, attribute) in ATTRIBUTES {
+                    if cell.modifier.contains(modifier) {
+                        text.push_str(&SetAttribute(attribute).to_string());
+                    }
+                }
+                current = style;
+            }
+            let symbol = cell.symbol();
+            text.push_str(symbol);
+            // A wide character covers the cells after it too.
+            x += Span::raw(symbol).width().max(1) as u16;
+        }
+        if current != plain {
+            text.push_str(&SetAttribute(Attribute::Reset).to_string());
+        }
+        text.push('\n');
+    }
+    text
+}
+
+/// Formats `[████░░] 80% 12s`, the percentage to `precision` decimal places,
+/// sizing the bar to fill `width` columns and dropping it entirely when
+/// there is no room for at least one cell.
+fn compact_bar(ratio: f64, elapsed: Duration, precision: usize, width: u16) -> String {
+    let ratio = ratio.clamp(0.0, 1.0);
+    let text = format!("{:.*}% {}s", precision, ratio * 100.0, elapsed.as_secs());
+    // Two brackets and the space before the text.
+    let Some(cells) = usize::from(width)
+        .checked_sub(text.len() + 3)
+        .filter(|&cells| cells > 0)
+    else {
+        return text;
+    };
+    let filled = (ratio * cells as f64).round() as usize;
+    format!(
+        "[{}{}] {text}",
+        "█".repeat(filled),
+        "░".repeat(cells - filled)
+    )
+}
+
+/// The part of `area` covered by the indeterminate bar at `frame`: a block a
+/// fifth of the width that bounces between the edges, two cells per tick.
+fn marquee(area: Rect, frame: usize) -> Rect {
+    let width = (area.width / 5).max(1).min(area.width);
+    let travel = usize::from(area.width - width);
+    let x = if travel == 0 {
+        0
+    } else {
+        let step = frame % travel * 2;
+        step.min(2 * travel - step)
+    };
+    Rect {
+        x: area.x + x as u16,
+        width,
+        ..area
+    }
+}
+
+/// Returns a `Rect` of the given percentage size centered within `area`.
+fn centered_rect(percent_x: u16, percent_y: u16, area: Rect) -> Rect {
+    let [_, middle, _] = Layout::vertical([
+        Constraint::Percentage((100 - percent_y) / 2),
+        Constraint::Percentage(percent_y),
+        Constraint::Percentage((100 - percent_y) / 2),
+    ])
+    .areas(area);
+    let [_, center, _] = Layout::horizontal([
+        Constraint::Percentage((100 - percent_x) / 2),
+        Constraint::Percentage(percent_x),
+        Constraint::Percentage((100 - percent_x) / 2),
+    ])
+    .areas(middle);
+    center
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crossterm::event::KeyEvent;
+    use ratatui::{backend::TestBackend, buffer::Cell};
+
+    #[test]
+    fn run_exits_cleanly_when_senders_hang_up() {
+        let mut terminal = Terminal::new(TestBackend::new(80, 20)).unwrap();
+        let mut app = ProgressApp::default();
+        let (tx, rx) = mpsc::channel();
+        drop(tx);
+
+        assert!(app.run(&mut terminal, rx).is_ok());
+        assert!(app.exit);
+    }
+
+    #[test]
+    fn run_exits_on_its_own_after_lingering_on_completion() {
+        let mut terminal = Terminal::new(TestBackend::new(80, 20)).unwrap();
+        let mut app = ProgressApp {
+            exit_on_complete: Some(Duration::from_millis(50)),
+            ..ProgressApp::new(["Download", "Test"])
+        };
+        app.handle_event(Event::Progress(0, 1.0)).unwrap();
+        app.handle_event(Event::Progress(1, 1.0)).unwrap();
+        assert!(!app.exit);
+
+        let (tx, rx) = mpsc::channel();
+        thread::spawn(move || run_tick_thread(tx, TICK_RATE));
+        let started = Instant::now();
+        assert!(app.run(&mut terminal, rx).is_ok());
+        assert!(started.elapsed() < Duration::from_secs(2));
+        assert!(row(terminal.backend().buffer(), 1).contains("Overall 100%"));
+    }
+
+    #[test]
+    fn run_applies_every_event_queued_between_frames() {
+        let mut terminal = Terminal::new(TestBackend::new(80, 20)).unwrap();
+        let mut app = ProgressApp {
+            tasks: vec![Task::new("stdin")],
+            frame_interval: Duration::from_secs(3600),
+            ..Default::default()
+        };
+        let (tx, rx) = mpsc::channel();
+        for step in 1..=100 {
+            tx.send(Event::Progress(0, f64::from(step) / 100.0))
+                .unwrap();
+        }
+        drop(tx);
+
+        app.run(&mut terminal, rx).unwrap();
+        assert_eq!(app.tasks[0].progress, 1.0);
+        assert_eq!(app.history.len(), 100);
+    }
+
+    #[test]
+    fn run_keeps_redrawing_while_no_events_arrive() {
+        let mut terminal = Terminal::new(CountingBackend {
+            inner: TestBackend::new(80, 20),
+            frames: 0,
+        })
+        .unwrap();
+        let mut app = ProgressApp {
+            tasks: vec![Task::new("stdin")],
+            poll_timeout: Duration::from_millis(5),
+            ..Default::default()
+        };
+        let (tx, rx) = mpsc::channel::<Event>();
+        // A source that stays quiet for a while, then goes away.
+        thread::spawn(move || {
+            thread::sleep(Duration::from_millis(100));
+            drop(tx);
+        });
+        let started = Instant::now();
+        app.run(&mut terminal, rx).unwrap();
+        assert!(app.has_exited());
+        assert!(app.last_draw.unwrap() >= started + Duration::from_millis(50));
+        // One frame per 5ms timeout over the 100ms, give or take scheduling.
+        assert!(terminal.backend().frames >= 5);
+        let buffer = terminal.backend().inner.buffer();
+        assert!((0..20).any(|y| row(buffer, y).contains("stdin")));
+    }
+
+    #[test]
+    fn run_repaints_a_paused_app_on_every_tick() {
+        let mut terminal = Terminal::new(CountingBackend {
+            inner: TestBackend::new(80, 20),
+            frames: 0,
+        })
+        .unwrap();
+        let mut task = Task::new("stdin");
+        task.paused = true;
+        let mut app = ProgressApp {
+            tasks: vec![task],
+            // Only the ticks can wake it, not the poll timeout.
+            poll_timeout: Duration::from_secs(3600),
+            frame_interval: Duration::ZERO,
+            ..Default::default()
+        };
+        let (tx, rx) = mpsc::channel();
+        let tick_tx = tx.clone();
+        thread::spawn(move || run_tick_thread(tick_tx, Duration::from_millis(10)));
+        thread::spawn(move || {
+            thread::sleep(Duration::from_millis(150));
+            for code in [KeyCode::Char('q'), KeyCode::Char('y')] {
+                tx.send(Event::Input(KeyEvent::from(code))).unwrap();
+            }
+        });
+        app.run(&mut terminal, rx).unwrap();
+        assert!(app.has_exited());
+        // A tick every 10ms over the 150ms, give or take scheduling.
+        assert!(app.spinner_frame >= 5);
+        assert!(terminal.backend().frames >= 5);
+    }
+
+    /// Connects `app` to a worker command channel, returning its far end.
+    fn with_workers(mut app: ProgressApp) -> (ProgressApp, mpsc::Receiver<WorkerCommand>) {
+        let (commands, command_rx) = mpsc::channel();
+        app.controls.commands = Some(commands);
+        (app, command_rx)
+    }
+
+    /// Renders `app` into an 80x20 buffer.
+    fn render(app: &ProgressApp) -> Buffer {
+        let mut terminal = Terminal::new(TestBackend::new(80, 20)).unwrap();
+        terminal.draw(|f| app.draw(f)).unwrap();
+        terminal.backend().buffer().clone()
+    }
+
+    fn row(buffer: &Buffer, y: u16) -> String {
+        (0..buffer.area.width)
+            .map(|x| buffer[(x, y)].symbol())
+            .collect()
+    }
+
+    #[test]
+    fn render_shows_the_title_and_an_empty_gauge_at_zero() {
+        let app = ProgressApp {
+            tasks: vec![Task::new("Download")],
+            ..Default::default()
+        };
+        let buffer = render(&app);
+
+        assert!(row(&buffer, 0).contains("Process Overview — Download"));
+        assert!(row(&buffer, 1).contains("Overall 0%"));
+        assert!(row(&buffer, 4).contains("Download"));
+        assert!(row(&buffer, 5).contains(" 0% (0.0s) "));
+        // None of the gauge is filled yet.
+        assert!(!row(&buffer, 5).contains('█'));
+    }
+
+    #[test]
+    fn labels_align_inside_the_bar_or_beside_it_without_squeezing_it() {
+        let area = Rect::new(2, 5, 76, 1);
+        let split = |width, align| split_label(area, width, align);
+        assert_eq!(split(10, LabelAlign::Center).1, Rect::new(35, 5, 10, 1));
+        assert_eq!(split(10, LabelAlign::Left).1, Rect::new(3, 5, 10, 1));
+        assert_eq!(split(10, LabelAlign::Right).1, Rect::new(67, 5, 10, 1));
+        assert_eq!(
+            split(10, LabelAlign::Outside),
+            (Rect::new(2, 5, 65, 1), Rect::new(68, 5, 10, 1))
+        );
+        // A long label is cut to leave the bar half the row.
+        assert_eq!(
+            split(200, LabelAlign::Outside),
+            (Rect::new(2, 5, 38, 1), Rect::new(41, 5, 37, 1))
+        );
+
+        let mut app = ProgressApp {
+            label_format: LabelTemplate::parse("{pct}%"),
+            label_align: LabelAlign::Outside,
+            ..ProgressApp::new(["Download"])
+        };
+        app.update_progress(0, 1.0 / 3.0);
+        let line = row(&render(&app), 5);
+        assert!(line.ends_with("   33%║│"), "{line}");
+        // The fill is a third of the bar, not of the row.
+        assert_eq!(line.matches('█').count(), 24);
+
+        app.label_align = LabelAlign::Right;
+        let line = row(&render(&app), 5);
+        assert!(line.ends_with(" 33% ║│"), "{line}");
+    }
+
+    #[test]
+    fn emoji_titles_are_centered_by_cells_not_chars() {
+        // The icon is two code points, an emoji and its variation selector,
+        // drawn two cells wide, so the heading is 35 cells.
+        let heading = "🛠️  Process Overview — 0/2 complete";
+        assert_eq!("🛠️".width(), 2);
+        assert_eq!(centered_padding(heading, 80), 22);
+        assert_eq!(centered_padding(heading, 20), 0);
+
+        // The emoji and the plain icon take as many cells, so the words land
+        // in the same place.
+        let mut app = ProgressApp::new(["Download", "Test"]);
+        for no_emoji in [false, true] {
+            app.no_emoji = no_emoji;
+            let buffer = render(&app);
+            assert_eq!(buffer[(26, 0)].symbol(), "P");
+            assert_ne!(buffer[(22, 0)].symbol(), " ");
+        }
+    }
+
+    #[test]
+    fn tall_gauges_share_the_task_list_without_dropping_below_three_rows() {
+        let inner = Rect::new(0, 0, 40, 12);
+        let app = |tasks: usize, gauge_height| ProgressApp {
+            gauge_height,
+            ..ProgressApp::new((0..tasks).map(|task| format!("Task {task}")))
+        };
+        assert_eq!(app(2, GAUGE_HEIGHT).gauge_rows(inner), GAUGE_HEIGHT);
+        assert_eq!(app(1, 20).gauge_rows(inner), 12);
+        assert_eq!(app(2, 5).gauge_rows(inner), 5);
+        assert_eq!(app(3, 5).gauge_rows(inner), 4);
+        assert_eq!(app(9, 5).gauge_rows(inner), GAUGE_HEIGHT);
+
+        // The label sits on the middle row of the taller bar.
+        let mut app = app(1, 5);
+        app.update_progress(0, 0.5);
+        let buffer = render(&app);
+        assert!(row(&buffer, 4).contains("Task 0"));
+        assert!(row(&buffer, 6).contains("50% ("));
+        assert!(row(&buffer, 8).contains("╚═"));
+    }
+
+    #[test]
+    fn tiny_terminals_ask_for_a_resize_until_they_grow() {
+        let app = ProgressApp::new(["Download"]);
+        let draw = |width, height| {
+            let mut buffer = Buffer::empty(Rect::new(0, 0, width, height));
+            (&app).render(buffer.area, &mut buffer);
+            buffer_to_text(&buffer)
+        };
+        let small = draw(30, MIN_HEIGHT - 1);
+        assert!(small.contains("Terminal too small —"));
+        assert!(small.contains("resize to at least 20x6"));
+        assert!(!small.contains("Download"));
+        assert!(draw(MIN_WIDTH - 1, 10).contains("Terminal too"));
+
+        let grown = draw(MIN_WIDTH, MIN_HEIGHT);
+        assert!(grown.contains("Download"));
+        assert!(!grown.contains("too small"));
+    }
+
+    #[test]
+    fn render_stays_inside_any_area_it_is_given() {
+        let mut app = ProgressApp::new(["Download", "Compile", "Test"]);
+        app.update_progress(0, 0.5);
+        let whole = Rect::new(0, 0, 60, 24);
+        for (width, height) in (0..=45)
+            .step_by(3)
+            .flat_map(|width| (0..=14).map(move |height| (width, height)))
+        {
+            let area = Rect::new(7, 3, width, height);
+            let mut buffer = Buffer::empty(whole);
+            (&app).render(area, &mut buffer);
+            for (x, y) in whole.positions().map(|p| (p.x, p.y)) {
+                if !area.contains(Position::new(x, y)) {
+                    assert_eq!(
+                        buffer[(x, y)],
+                        Cell::EMPTY,
+                        "{width}x{height} drew at {x},{y}"
+                    );
+                }
+            }
+        }
+
+        let mut buffer = Buffer::empty(whole);
+        (&app).render(Rect::new(7, 3, 45, 14), &mut buffer);
+        assert!(row(&buffer, 7).contains("Download"));
+        assert!(row(&buffer, 8).contains("50% ("));
+    }
+
+    #[test]
+    fn render_shows_a_completed_gauge_at_one_hundred_percent() {
+        let mut app = ProgressApp {
+            tasks: vec![Task::new("Download")],
+            ..Default::default()
+        };
+        app.update_progress(0, 1.0);
+        let buffer = render(&app);
+
+        assert!(row(&buffer, 1).contains("Overall 100%"));
+        assert!(row(&buffer, 4).contains("Download"));
+        assert!(row(&buffer, 5).contains("✓ Complete"));
+        // Between the borders, every cell is either filled or part of the
+        // label; the completed log takes the right-hand 30 columns.
+        assert!((2..48).all(|x| {
+            let cell = &buffer[(x, 5)];
+            (cell.symbol() == "█" && cell.fg == app.theme.complete) || cell.bg == app.theme.complete
+        }));
+    }
+
+    #[test]
+    fn curve_eases_the_fill_but_not_the_label() {
+        let mut app = ProgressApp {
+            tasks: vec![Task::new("Download")],
+            curve: 2.0,
+            ..Default::default()
+        };
+        app.update_progress(0, 0.5);
+        let buffer = render(&app);
+
+        assert!(row(&buffer, 5).contains(" 50% ("));
+        // A quarter of the 76 interior cells, rather than half.
+        let filled = (2..78).filter(|&x| buffer[(x, 5)].symbol() == "█").count();
+        assert_eq!(filled, 19);
+    }
+
+    #[test]
+    fn vertical_gauges_fill_upwards_under_their_label() {
+        let mut app = ProgressApp::new(["Download"]);
+        app.vertical = true;
+        app.update_progress(0, 0.5);
+        let buffer = render(&app);
+
+        let column = |y| (2..8).map(|x| buffer[(x, y)].symbol()).collect::<String>();
+        assert_eq!(column(5).trim(), "50%");
+        // Half of the eight rows under the label, from the bottom.
+        assert!((6..10).all(|y| column(y).trim().is_empty()));
+        assert!((10..14).all(|y| column(y) == "██████"));
+
+        let mut empty = Buffer::empty(Rect::new(0, 0, 6, 1));
+        draw_vertical_bar(
+            1.0,
+            Style::default(),
+            "✓",
+            true,
+            Rect::new(0, 0, 6, 0),
+            &mut empty,
+        );
+        assert_eq!(empty, Buffer::empty(Rect::new(0, 0, 6, 1)));
+    }
+
+    #[test]
+    fn precision_applies_to_vertical_compact_and_overall_percentages() {
+        let mut app = ProgressApp {
+            precision: 1,
+            vertical: true,
+            ..ProgressApp::new(["Download"])
+        };
+        app.update_progress(0, 0.125);
+        let shows = |app: &ProgressApp, text: &str| {
+            let buffer = render(app);
+            (0..20).any(|y| row(&buffer, y).contains(text))
+        };
+        assert!(shows(&app, "Overall 12.5%"));
+        assert!(shows(&app, "║12.5% ║"));
+
+        app.compact = true;
+        assert!(row(&render(&app), 0).contains("] 12.5% "));
+    }
+
+    #[test]
+    fn phases_advance_in_order_and_add_up_by_weight() {
+        let mut app = ProgressApp {
+            label_format: LabelTemplate::parse(label::PHASE_LABEL_FORMAT),
+            ..ProgressApp::new(["Setup"])
+        };
+        app.tasks[0].phases = [("Download", 1.0), ("Install", 3.0)]
+            .map(|(name, weight)| Phase {
+                name: name.to_string(),
+                weight,
+                progress: 0.0,
+            })
+            .into();
+        let (mut app, commands) = with_workers(app);
+
+        app.report_progress(0, None, 0.5);
+        assert_eq!(app.tasks[0].progress, 0.125);
+        app.report_progress(0, None, 1.0);
+        app.report_progress(0, None, 1.0);
+        assert_eq!((app.tasks[0].phase, app.tasks[0].progress), (0, 0.25));
+        // The demo worker starts over for the next phase, whose reports the
+        // repeated 100% above didn't skip.
+        assert!(matches!(commands.try_recv(), Ok(WorkerCommand::Reset(0))));
+        assert!(commands.try_recv().is_err());
+
+        app.report_progress(0, None, 0.5);
+        assert_eq!((app.tasks[0].phase, app.tasks[0].progress), (1, 0.625));
+        let buffer = render(&app);
+        assert!(row(&buffer, 4).contains("▸ Install (2/2)"));
+        assert!(row(&buffer, 5).contains("Install… 50%"));
+
+        app.go_to(0.125);
+        assert_eq!(app.tasks[0].phase, 0);
+        assert_eq!(app.tasks[0].reported_progress(), 0.5);
+        app.perform(Action::Reset);
+        assert_eq!(app.tasks[0].phase_total(), 0.0);
+    }
+
+    #[test]
+    fn out_of_range_progress_is_clamped_and_nan_ignored() {
+        let mut app = ProgressApp::new(["Download"]);
+        app.handle_event(Event::Progress(0, -0.5)).unwrap();
+        assert_eq!(app.tasks[0].progress, 0.0);
+        assert!(
+            app.status
+                .ends_with("Clamped invalid progress -0.5 for Download")
+        );
+
+        app.handle_event(Event::Progress(0, 0.5)).unwrap();
+        app.handle_event(Event::Progress(0, f64::NAN)).unwrap();
+        app.set_progress(0, f64::INFINITY);
+        assert_eq!(app.tasks[0].progress, 0.5);
+        assert!(
+            app.status
+                .ends_with("Ignored invalid progress inf for Download")
+        );
+        assert!(row(&render(&app), 5).contains("50%"));
+
+        app.set_progress(0, 2.0);
+        assert_eq!(app.tasks[0].progress, 1.0);
+        assert!(app.tasks[0].completed);
+        assert!(app.overall_progress() <= 1.0);
+        assert!(row(&render(&app), 5).contains("Complete"));
+    }
+
+    #[test]
+    fn byte_counts_drive_progress_and_animate_without_a_total() {
+        let mut app = ProgressApp {
+            label_format: LabelTemplate::parse(label::BYTES_LABEL_FORMAT),
+            ..ProgressApp::new(["Download"])
+        };
+        app.handle_event(Event::Bytes(0, 45_200_000, 100_000_000))
+            .unwrap();
+        assert!((app.tasks[0].progress - 0.452).abs() < 1e-9);
+        assert!(row(&render(&app), 5).contains("45.2 MB / 100 MB (45%)"));
+
+        app.handle_event(Event::Bytes(0, 52_000_000, 0)).unwrap();
+        assert!(app.tasks[0].is_indeterminate());
+        assert!(row(&render(&app), 5).contains("52 MB so far ("));
+    }
+
+    #[test]
+    fn gauges_mark_the_peak_until_the_task_resets() {
+        let mut app = ProgressApp::new(["Download"]);
+        app.update_progress(0, 0.75);
+        app.update_progress(0, 0.25);
+        // Three quarters of the 76 interior cells in from the left border.
+        assert_eq!(render(&app)[(59, 5)].symbol(), "│");
+
+        app.perform(Action::Reset);
+        // Only the outer border is left.
+        assert_eq!(row(&render(&app), 5).matches('│').count(), 2);
+    }
+
+    #[test]
+    fn milestones_ring_once_per_decile_despite_wobbling() {
+        let mut app = ProgressApp::default();
+        let rang: Vec<bool> = [0.05, 0.1, 0.12, 0.09, 0.11, 0.35, 0.0, 0.1, 1.0]
+            .into_iter()
+            .map(|overall| app.passed_milestone(overall))
+            .collect();
+        assert_eq!(
+            rang,
+            [false, true, false, false, false, true, false, true, true]
+        );
+        assert_eq!(app.beep_milestone, 9);
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn failed_log_writes_stop_logging_with_a_status_message() {
+        // Every write to /dev/full fails for want of space.
+        let mut app = ProgressApp {
+            progress_log: Some(ProgressLog::open(Path::new("/dev/full")).unwrap()),
+            ..ProgressApp::new(["Download"])
+        };
+        app.update_progress(0, 0.5);
+        app.log_progress(true);
+        assert!(app.progress_log.is_none());
+        assert!(app.status.contains("] Stopped logging to /dev/full: "));
+        // Nothing left to fail on the next tick.
+        app.handle_event(Event::Tick).unwrap();
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn on_complete_runs_once_per_completion_and_reports_failures() {
+        let path = std::env::temp_dir().join(format!("on-complete-{}.txt", process::id()));
+        let mut app = ProgressApp {
+            on_complete: Some(format!(
+                "echo \"$PROGRESS_TASK\" >> {}; exit 3",
+                path.display()
+            )),
+            ..ProgressApp::new(["Download"])
+        };
+        app.update_progress(0, 1.0);
+        app.update_progress(0, 1.0);
+
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while !app.status.contains("failed") && Instant::now() < deadline {
+            thread::sleep(Duration::from_millis(10));
+            app.handle_event(Event::Tick).unwrap();
+        }
+        assert!(
+            app.status.contains("failed: exit status: 3"),
+            "{}",
+            app.status
+        );
+        assert_eq!(fs::read_to_string(&path).unwrap(), "Download\n");
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn stalled_tasks_are_flagged_until_progress_advances() {
+        let mut app = ProgressApp {
+            tasks: vec![Task::new("Download")],
+            stall_after: Some(Duration::from_secs(5)),
+            ..Default::default()
+        };
+        app.update_progress(0, 0.4);
+        app.tasks[0].last_progress_at -= Duration::from_secs(5);
+        let buffer = render(&app);
+        assert!(row(&buffer, 5).contains("⚠ stalled"));
+        assert_eq!(buffer[(1, 4)].fg, app.theme.warning);
+
+        // Repeating the same value isn't progress.
+        app.update_progress(0, 0.4);
+        assert!(app.tasks[0].is_stalled(Duration::from_secs(5)));
+        app.update_progress(0, 0.5);
+        assert!(!row(&render(&app), 5).contains("stalled"));
+    }
+
+    #[test]
+    fn troubled_tasks_blink_until_a_key_acknowledges_them() {
+        let mut app = ProgressApp {
+            tasks: vec![Task::new("Download")],
+            stall_after: Some(Duration::from_secs(5)),
+            ..Default::default()
+        };
+        app.update_progress(0, 0.4);
+        app.tasks[0].last_progress_at -= Duration::from_secs(5);
+        let border = |app: &mut ProgressApp| {
+            app.handle_event(Event::Tick).unwrap();
+            render(app)[(1, 4)].fg
+        };
+        let blinks = [border(&mut app), border(&mut app)];
+        assert!(app.alert);
+        assert!(blinks.contains(&app.theme.warning));
+        assert!(blinks.iter().any(|color| *color != app.theme.warning));
+
+        app.handle_key(KeyEvent::from(KeyCode::Char('j'))).unwrap();
+        assert!(!app.alert);
+        assert_eq!([border(&mut app), border(&mut app)], [app.theme.warning; 2]);
+
+        // Recovering and stalling again alerts afresh.
+        app.update_progress(0, 0.5);
+        border(&mut app);
+        app.tasks[0].last_progress_at -= Duration::from_secs(5);
+        border(&mut app);
+        assert!(app.alert);
+    }
+
+    #[test]
+    fn compact_bar_fits_the_width_and_drops_the_bar_when_narrow() {
+        let elapsed = Duration::from_secs(12);
+        assert_eq!(compact_bar(0.8, elapsed, 0, 20), "[████████░░] 80% 12s");
+        assert_eq!(compact_bar(0.8, elapsed, 0, 11), "[█] 80% 12s");
+        assert_eq!(compact_bar(0.8, elapsed, 0, 10), "80% 12s");
+        assert_eq!(compact_bar(0.8, elapsed, 0, 0), "80% 12s");
+    }
+
+    #[test]
+    fn go_to_prompt_jumps_the_selected_task_and_its_worker() {
+        let (mut app, commands) = with_workers(ProgressApp::new(["Download"]));
+        for code in [
+            KeyCode::Char('g'),
+            KeyCode::Char('7'),
+            KeyCode::Char('x'),
+            KeyCode::Char('9'),
+            KeyCode::Backspace,
+            KeyCode::Char('5'),
+        ] {
+            app.handle_key(KeyEvent::from(code)).unwrap();
+        }
+        assert_eq!(app.input_buffer, "75");
+        assert!(row(&render(&app), 19).contains("75%"));
+
+        app.handle_key(KeyEvent::from(KeyCode::Enter)).unwrap();
+        assert!(!app.goto_prompt);
+        assert_eq!(app.tasks[0].progress, 0.75);
+        assert_eq!(
+            commands.try_iter().collect::<Vec<_>>(),
+            [WorkerCommand::JumpTo(0, 0.75)]
+        );
+
+        for code in ['g', '1', '5', '0'] {
+            app.handle_key(KeyEvent::from(KeyCode::Char(code))).unwrap();
+        }
+        app.handle_key(KeyEvent::from(KeyCode::Enter)).unwrap();
+        assert_eq!(app.tasks[0].progress, 0.75);
+        assert_eq!(commands.try_recv().ok(), None);
+    }
+
+    #[test]
+    fn arrow_keys_take_a_task_over_until_it_is_resumed() {
+        let (mut app, commands) = with_workers(ProgressApp::new(["Download"]));
+        for code in [KeyCode::Left, KeyCode::Right] {
+            app.handle_key(KeyEvent::from(code)).unwrap();
+        }
+        assert_eq!(app.tasks[0].progress, MANUAL_STEP);
+        assert_eq!(
+            commands.try_iter().collect::<Vec<_>>(),
+            [
+                WorkerCommand::Stop(0),
+                WorkerCommand::JumpTo(0, 0.0),
+                WorkerCommand::JumpTo(0, MANUAL_STEP),
+            ]
+        );
+        // The source no longer moves it, and the label says why.
+        app.handle_event(Event::Progress(0, 0.9)).unwrap();
+        assert_eq!(app.tasks[0].progress, MANUAL_STEP);
+        assert!(row(&render(&app), 5).contains("(manual)"));
+
+        app.handle_key(KeyEvent::from(KeyCode::Char(' '))).unwrap();
+        assert!(!app.tasks[0].manual);
+        assert_eq!(commands.try_recv().ok(), Some(WorkerCommand::Start(0)));
+        app.handle_event(Event::Progress(0, 0.9)).unwrap();
+        assert_eq!(app.tasks[0].progress, 0.9);
+    }
+
+    #[test]
+    fn accessible_gauges_use_fill_patterns() {
+        let mut app = ProgressApp {
+            tasks: vec![Task::new("Download")],
+            accessible: true,
+            ..Default::default()
+        };
+        app.update_progress(0, 0.5);
+        let gauge = row(&render(&app), 5);
+
+        assert!(gauge.contains(" 50% ("));
+        assert!(gauge.starts_with("│║▓▓▓"));
+        assert!(gauge.ends_with("░░░║│"));
+    }
+
+    #[test]
+    fn gradient_gauges_color_each_cell_by_position_behind_a_readable_label() {
+        let mut app = ProgressApp {
+            tasks: vec![Task::new("Download")],
+            gradient: true,
+            ..Default::default()
+        };
+        app.update_progress(0, 0.5);
+        let buffer = render(&app);
+        // The gauge's inner 76 columns start at x = 2, half of them filled.
+        assert_eq!(buffer[(2, 5)].bg, gradient_color(0.5 / 76.0));
+        assert_eq!(buffer[(39, 5)].bg, gradient_color(37.5 / 76.0));
+        assert_eq!(buffer[(40, 5)].bg, Color::Reset);
+        assert!(row(&buffer, 5).contains(" 50% ("));
+        let digit = (0..80).find(|&x| buffer[(x, 5)].symbol() == "5").unwrap();
+        assert_eq!(buffer[(digit, 5)].fg, readable_on(buffer[(digit, 5)].bg));
+
+        assert_eq!(gradient_color(0.0), Color::Rgb(215, 50, 40));
+        assert_eq!(gradient_color(0.5), Color::Rgb(230, 190, 30));
+        assert_eq!(gradient_color(1.0), Color::Rgb(40, 175, 70));
+        assert_eq!(readable_on(gradient_color(0.0)), Color::White);
+        assert_eq!(readable_on(gradient_color(0.5)), Color::Black);
+
+        // Finished tasks keep the completion color.
+        app.update_progress(0, 1.0);
+        assert_eq!(render(&app)[(2, 5)].bg, Color::Reset);
+    }
+
+    #[test]
+    fn comparisons_say_which_task_leads_and_highlight_its_label() {
+        let mut app = ProgressApp {
+            compare: true,
+            ..ProgressApp::new(["Debug", "Release"])
+        };
+        app.update_progress(0, 0.3);
+        app.update_progress(1, 0.42);
+        let buffer = render(&app);
+        // Straight under the second gauge.
+        assert!(row(&buffer, 10).contains("Release ahead by 12% after"));
+        let label_x = |y| row(&buffer, y).chars().position(|c| c == '%').unwrap() as u16;
+        assert_eq!(buffer[(label_x(8), 8)].fg, app.theme.accent);
+        assert_ne!(buffer[(label_x(5), 5)].fg, app.theme.accent);
+
+        app.update_progress(0, 0.42);
+        let buffer = render(&app);
+        assert!(row(&buffer, 10).contains("Level at 42% after"));
+        assert_ne!(buffer[(label_x(8), 8)].fg, app.theme.accent);
+
+        // Without exactly two tasks there is nothing to compare.
+        app.tasks.push(Task::new("Profile"));
+        assert!(!(0..20).any(|y| row(&render(&app), y).contains("ahead by")));
+    }
+
+    #[test]
+    fn milestones_mark_the_bottom_border_and_light_up_once_passed() {
+        let mut app = ProgressApp {
+            tasks: vec![Task::new("Download")],
+            milestones: vec![0.0, 0.5, 1.0],
+            ..Default::default()
+        };
+        app.update_progress(0, 0.6);
+        let buffer = render(&app);
+        // The gauge's inner 76 columns start at x = 2, both edges included.
+        let marks: Vec<_> = (0..80)
+            .filter(|&x| matches!(buffer[(x, 6)].symbol(), "▲" | "△"))
+            .map(|x| (x, buffer[(x, 6)].symbol()))
+            .collect();
+        assert_eq!(marks, [(2, "▲"), (40, "▲"), (77, "△")]);
+        assert_eq!(buffer[(40, 6)].fg, buffer[(2, 5)].fg);
+        assert!(buffer[(77, 6)].modifier.contains(Modifier::DIM));
+    }
+
+    #[test]
+    fn merge_strategies_combine_interleaved_reports_for_one_task() {
+        // Two sources taking turns, one ahead of the other.
+        let reports = [0.25, 0.75, 0.5, 0.875, 0.625];
+        for (merge, expected) in [
+            (MergeStrategy::Latest, [0.25, 0.75, 0.5, 0.875, 0.625]),
+            (MergeStrategy::Max, [0.25, 0.75, 0.75, 0.875, 0.875]),
+            (MergeStrategy::Min, [0.25, 0.25, 0.25, 0.25, 0.25]),
+            (MergeStrategy::Average, [0.25, 0.5, 0.625, 0.6875, 0.75]),
+        ] {
+            let mut app = ProgressApp {
+                tasks: vec![Task::new("Build")],
+                merge,
+                ..Default::default()
+            };
+            let seen: Vec<f64> = reports
+                .iter()
+                .enumerate()
+                .map(|(turn, &report)| {
+                    app.handle_event(Event::SourceProgress(0, turn % 2, report))
+                        .unwrap();
+                    app.tasks[0].progress
+                })
+                .collect();
+            assert_eq!(seen, expected, "{merge:?}");
+
+            // A reset starts over from the next report alone.
+            app.tasks[0].reset();
+            app.handle_event(Event::Progress(0, 0.125)).unwrap();
+            assert_eq!(app.tasks[0].progress, 0.125, "{merge:?}");
+        }
+    }
+
+    #[test]
+    fn averages_weigh_every_source_alike_however_often_it_reports() {
+        let mut app = ProgressApp {
+            tasks: vec![Task::new("Build")],
+            merge: MergeStrategy::Average,
+            ..Default::default()
+        };
+        // One worker reports once, the other four times in a row.
+        app.handle_event(Event::SourceProgress(0, 1, 0.875))
+            .unwrap();
+        for report in [0.125, 0.25, 0.375, 0.5] {
+            app.handle_event(Event::SourceProgress(0, 0, report))
+                .unwrap();
+        }
+        assert_eq!(app.tasks[0].progress, (0.5 + 0.875) / 2.0);
+
+        // Reports not told apart by source count as one more source.
+        app.handle_event(Event::Progress(0, 0.0)).unwrap();
+        assert_eq!(app.tasks[0].progress, (0.5 + 0.875) / 3.0);
+    }
+
+    #[test]
+    fn labels_show_the_change_since_the_last_tick_in_green_or_red() {
+        let mut app = ProgressApp {
+            tasks: vec![Task::new("Download")],
+            ..Default::default()
+        };
+        // The indicator comes last, after the elapsed time's parentheses.
+        let sign = |buffer: &Buffer| {
+            let x = (0..80)
+                .rev()
+                .find(|&x| buffer[(x, 5)].symbol() == "(")
+                .unwrap();
+            let cell = &buffer[(x + 1, 5)];
+            (cell.symbol().to_string(), cell.fg)
+        };
+        app.update_progress(0, 0.4);
+        app.handle_event(Event::Tick).unwrap();
+        app.update_progress(0, 0.423);
+        app.handle_event(Event::Tick).unwrap();
+        let buffer = render(&app);
+        assert!(row(&buffer, 5).contains(" (+2.3%)"));
+        assert_eq!(sign(&buffer), ("+".to_string(), app.theme.gauge[0]));
+
+        app.update_progress(0, 0.4);
+        app.handle_event(Event::Tick).unwrap();
+        let buffer = render(&app);
+        assert!(row(&buffer, 5).contains(" (-2.3%)"));
+        assert_eq!(sign(&buffer), ("-".to_string(), app.theme.gauge[4]));
+
+        // A task that stood still over the last tick drops the indicator.
+        app.handle_event(Event::Tick).unwrap();
+        assert!(!row(&render(&app), 5).contains("%)"));
+    }
+
+    #[test]
+    fn completed_tasks_are_logged_newest_first_beside_the_gauges() {
+        let mut app = ProgressApp {
+            tasks: vec![Task::new("Download"), Task::new("Test")],
+            ..Default::default()
+        };
+        assert!(!row(&render(&app), 3).contains("Completed"));
+
+        app.update_progress(1, 1.0);
+        app.update_progress(0, 1.0);
+        // Later updates at 100% don't log the task again.
+        app.update_progress(0, 1.0);
+        let names: Vec<&str> = app
+            .completed_log
+            .iter()
+            .map(|(name, _)| name.as_str())
+            .collect();
+        assert_eq!(names, ["Test", "Download"]);
+
+        let buffer = render(&app);
+        assert!(row(&buffer, 3).ends_with("┐"));
+        assert!(row(&buffer, 3).contains("Completed"));
+        assert!(row(&buffer, 4).contains("✓ Download 0s ago"));
+        assert!(row(&buffer, 5).contains("✓ Test 0s ago"));
+    }
+
+    #[test]
+    fn b_cycles_the_border_style() {
+        let mut app = ProgressApp {
+            tasks: vec![Task::new("Download"), Task::new("Test")],
+            ..Default::default()
+        };
+        // The selected first task keeps its double border throughout.
+        assert!(row(&render(&app), 7).starts_with("│┌"));
+
+        app.handle_key(KeyEvent::from(KeyCode::Char('b'))).unwrap();
+        assert_eq!(app.border_set, border::ROUNDED);
+        let buffer = render(&app);
+        // The blocks around the gauges and the history follow suit.
+        assert!(row(&buffer, 3).starts_with("╭"));
+        assert!(row(&buffer, 4).starts_with("│╔"));
+        assert!(row(&buffer, 7).starts_with("│╭"));
+        assert!((0..20).any(|y| row(&buffer, y).starts_with("╭History")));
+        app.show_help = true;
+        assert!((0..20).any(|y| row(&render(&app), y).contains("╭ Help ─")));
+        app.show_help = false;
+
+        for _ in 1..BORDER_STYLES.len() {
+            app.handle_key(KeyEvent::from(KeyCode::Char('b'))).unwrap();
+        }
+        assert_eq!(app.border_set, border::PLAIN);
+    }
+
+    #[test]
+    fn i_hides_the_key_hints_and_gives_their_row_to_the_gauges() {
+        let mut app = ProgressApp::new((0..5).map(|i| format!("Task {i}")));
+        let footer = |buffer: &Buffer| (0..20).any(|y| row(buffer, y).contains("Press C"));
+        assert!(footer(&render(&app)));
+        assert_eq!(app.visible_tasks.get(), 3);
+
+        app.handle_key(KeyEvent::from(KeyCode::Char('i'))).unwrap();
+        assert!(!footer(&render(&app)));
+        assert_eq!(app.visible_tasks.get(), 4);
+
+        // The quit prompt still needs somewhere to go.
+        app.handle_key(KeyEvent::from(KeyCode::Char('q'))).unwrap();
+        assert!((0..20).any(|y| row(&render(&app), y).contains("Quit? (y/n)")));
+    }
+
+    #[test]
+    fn tabs_switch_between_the_gauges_log_and_stats() {
+        let mut app = ProgressApp::new(["Download", "Test"]);
+        app.update_progress(0, 0.4);
+        let shows = |app: &ProgressApp, text: &str| {
+            let buffer = render(app);
+            (0..20).any(|y| row(&buffer, y).contains(text))
+        };
+        assert!(row(&render(&app), 2).contains("Progress │ Log │ Stats"));
+        assert!(shows(&app, "Background Processes"));
+
+        app.handle_key(KeyEvent::from(KeyCode::Tab)).unwrap();
+        assert_eq!(app.active_tab, Tab::Log);
+        assert!(shows(&app, "Showing the log tab"));
+        assert!(!shows(&app, "Background Processes"));
+
+        app.handle_key(KeyEvent::from(KeyCode::Char('3'))).unwrap();
+        assert_eq!(app.active_tab, Tab::Stats);
+        assert!(shows(&app, "Task      Download"));
+        assert!(shows(&app, "Progress  40%"));
+
+        app.handle_key(KeyEvent::from(KeyCode::Tab)).unwrap();
+        assert_eq!(app.active_tab, Tab::Progress);
+    }
+
+    #[test]
+    fn groups_sum_up_their_tasks_indented_beneath_them_and_collapse() {
+        let mut app = ProgressApp::new(["Compile", "Link", "Test", "Lint"]);
+        let build = app.group("Build", &[0, 1]);
+        let checks = app.group("Checks", &[2, 3]);
+        let release = app.group("Release", &[build, checks]);
+        // Already grouped tasks are left where they are.
+        let again = app.group("Again", &[0]);
+        assert!(app.tasks[again].children.is_empty());
+        app.tasks.pop();
+
+        app.handle_event(Event::Progress(0, 1.0)).unwrap();
+        app.handle_event(Event::Progress(1, 0.5)).unwrap();
+        app.handle_event(Event::Progress(2, 0.5)).unwrap();
+        // Groups have no source of their own.
+        app.handle_event(Event::Progress(build, 0.0)).unwrap();
+        assert_eq!(app.progress(build), Some(0.75));
+        assert_eq!(app.progress(checks), Some(0.25));
+        assert_eq!(app.progress(release), Some(0.5));
+        assert_eq!(app.overall_progress(), 0.5);
+
+        let mut terminal = Terminal::new(TestBackend::new(80, 40)).unwrap();
+        terminal.draw(|f| app.draw(f)).unwrap();
+        let buffer = terminal.backend().buffer();
+        // Each level is indented two more columns than the one above.
+        let indents: Vec<u16> = (0..40)
+            .filter(|&y| buffer[(0, y)].symbol() == "│")
+            .filter_map(|y| (1..40).find(|&x| matches!(buffer[(x, y)].symbol(), "┌" | "╔")))
+            .collect();
+        assert_eq!(indents, [1, 3, 5, 5, 3, 5, 5]);
+        assert!(row(buffer, 4).contains("▾ Release"));
+
+        // Enter on a task collapses its group, and j skips what is hidden.
+        app.select(1);
+        app.handle_key(KeyEvent::from(KeyCode::Enter)).unwrap();
+        assert_eq!(app.selected, build);
+        assert!(app.tasks[build].collapsed);
+        assert_eq!(app.visible_order(), [release, build, checks, 2, 3]);
+        app.handle_key(KeyEvent::from(KeyCode::Char('j'))).unwrap();
+        assert_eq!(app.selected, checks);
+        assert!(app.status.contains("(3/5)"));
+
+        // Pausing a group pauses its tasks, which can't be moved through it.
+        app.perform(Action::TogglePause);
+        assert!(app.tasks[2].paused && app.tasks[3].paused);
+        app.perform(Action::StepForward);
+        assert_eq!(app.progress(checks), Some(0.25));
+        assert!(app.status.contains("select one of them"));
+
+        // A child going back reopens its finished group.
+        app.handle_event(Event::Progress(1, 1.0)).unwrap();
+        assert!(app.tasks[build].completed);
+        app.select(0);
+        app.perform(Action::Reset);
+        assert!(!app.tasks[build].completed);
+        assert_eq!(app.progress(build), Some(0.5));
+    }
+
+    #[test]
+    fn deep_task_trees_stop_indenting_at_half_the_width() {
+        let mut app = ProgressApp::new(["Leaf"]);
+        let mut child = 0;
+        for level in 0..40 {
+            child = app.group(format!("Level {level}"), &[child]);
+        }
+        app.select(0);
+        let mut terminal = Terminal::new(TestBackend::new(40, 20)).unwrap();
+        terminal.draw(|f| app.draw(f)).unwrap();
+        let buffer = terminal.backend().buffer();
+        let leaf = (0..20).find(|&y| row(buffer, y).contains("Leaf")).unwrap();
+        // Half of the 38 columns inside the outer border.
+        let left = (1..40).find(|&x| buffer[(x, leaf)].symbol() != " ");
+        assert_eq!(left, Some(1 + 19));
+    }
+
+    #[test]
+    fn digits_jump_to_tasks_over_the_gauges_and_switch_tabs_elsewhere() {
+        let mut app = ProgressApp::new(["Download", "Compile", "Test", "Package"]);
+        app.handle_key(KeyEvent::from(KeyCode::Char('3'))).unwrap();
+        assert_eq!((app.selected, app.active_tab), (2, Tab::Progress));
+        assert!(row(&render(&app), 19).contains("Selected Test (3/4)"));
+        // Past the last task lands on it.
+        app.handle_key(KeyEvent::from(KeyCode::Char('9'))).unwrap();
+        assert_eq!(app.selected, 3);
+
+        app.handle_key(KeyEvent::from(KeyCode::Tab)).unwrap();
+        app.handle_key(KeyEvent::from(KeyCode::Char('1'))).unwrap();
+        assert_eq!((app.selected, app.active_tab), (3, Tab::Progress));
+        app.handle_key(KeyEvent::from(KeyCode::Char('1'))).unwrap();
+        assert_eq!(app.selected, 0);
+
+        // The help lists the digits for what they do on the tab showing.
+        let help = |app: &ProgressApp| {
+            let mut terminal = Terminal::new(TestBackend::new(80, 40)).unwrap();
+            terminal.draw(|f| app.draw(f)).unwrap();
+            let buffer = terminal.backend().buffer();
+            (0..40)
+                .map(|y| row(buffer, y))
+                .collect::<Vec<_>>()
+                .join("\n")
+        };
+        app.handle_key(KeyEvent::from(KeyCode::Char('?'))).unwrap();
+        assert!(help(&app).contains("1-9  Jump to task 1 to 9"));
+        assert!(!help(&app).contains("Progress, log or stats tab"));
+        app.handle_key(KeyEvent::from(KeyCode::Char('2'))).unwrap();
+        assert_eq!((app.selected, app.active_tab), (1, Tab::Progress));
+
+        app.handle_key(KeyEvent::from(KeyCode::Tab)).unwrap();
+        assert!(help(&app).contains("1/2/3  Progress, log or stats tab"));
+        assert!(!help(&app).contains("Jump to task"));
+        app.handle_key(KeyEvent::from(KeyCode::Char('2'))).unwrap();
+        assert_eq!((app.selected, app.active_tab), (1, Tab::Log));
+    }
+
+    #[test]
+    fn progress_settling_just_short_of_one_still_completes() {
+        // Ten steps of 0.1 add up to a hair under 1.0.
+        let sum = (0..10).fold(0.0, |sum, _| sum + 0.1);
+        assert!(sum < 1.0);
+        let mut app = ProgressApp::new(["Sum", "Slow"]);
+        app.handle_event(Event::Progress(0, sum)).unwrap();
+        app.handle_event(Event::Progress(1, 0.99999)).unwrap();
+        assert!(
+            app.tasks
+                .iter()
+                .all(|task| task.completed && task.progress == 1.0)
+        );
+        assert_eq!(app.completed_log.len(), 2);
+        assert!(row(&render(&app), 5).contains("✓ Complete"));
+
+        // A phase ending just short hands over to the next.
+        let mut app = ProgressApp::new(["Install"]);
+        app.tasks[0].phases = ["Download", "Extract"]
+            .into_iter()
+            .map(|name| Phase {
+                name: name.to_string(),
+                weight: 1.0,
+                progress: 0.0,
+            })
+            .collect();
+        app.report_progress(0, None, 0.99999);
+        app.report_progress(0, None, 0.5);
+        assert_eq!((app.tasks[0].phase, app.tasks[0].progress), (1, 0.75));
+
+        // Without a tolerance only 1.0 itself will do.
+        let mut app = ProgressApp {
+            complete_tolerance: 0.0,
+            ..ProgressApp::new(["Exact"])
+        };
+        app.update_progress(0, sum);
+        assert!(!app.tasks[0].completed);
+    }
+
+    #[test]
+    fn pausing_everything_and_back_leaves_tasks_paused_by_hand_paused() {
+        let mut app = ProgressApp::new(["Download", "Compile", "Test"]);
+        let (commands, command_rx) = mpsc::channel();
+        app.controls.commands = Some(commands);
+        let press = |app: &mut ProgressApp, key| app.handle_key(KeyEvent::from(KeyCode::Char(key)));
+        press(&mut app, 'j').unwrap();
+        press(&mut app, ' ').unwrap();
+        press(&mut app, 'P').unwrap();
+        assert!(app.tasks.iter().all(|task| task.paused));
+        assert!(app.status.contains("Paused 2 tasks"));
+        assert!(row(&render(&app), 3).contains("All paused • Speed 1.00x"));
+
+        // One resumed by hand in between stays running.
+        press(&mut app, 'k').unwrap();
+        press(&mut app, ' ').unwrap();
+        press(&mut app, 'P').unwrap();
+        let paused: Vec<_> = app.tasks.iter().map(|task| task.paused).collect();
+        assert_eq!(paused, [false, true, false]);
+        assert!(app.status.contains("Resumed 1 task"));
+        assert!(!row(&render(&app), 3).contains("All paused"));
+
+        let sent: Vec<_> = command_rx.try_iter().collect();
+        assert_eq!(
+            sent,
+            [
+                WorkerCommand::Stop(1),
+                WorkerCommand::Stop(0),
+                WorkerCommand::Stop(2),
+                WorkerCommand::Start(0),
+                WorkerCommand::Start(2),
+            ]
+        );
+    }
+
+    #[test]
+    fn undo_takes_back_color_and_view_changes_newest_first() {
+        let mut app = ProgressApp::new(["Download", "Test"]);
+        let press = |app: &mut ProgressApp, key| app.handle_key(KeyEvent::from(KeyCode::Char(key)));
+        for key in ['c', 'c', 't', 'j', 'i'] {
+            press(&mut app, key).unwrap();
+        }
+        assert_eq!(app.tasks[0].color_index, 2);
+        // Moving the selection isn't undone, and each color goes back on its
+        // own task.
+        press(&mut app, 'u').unwrap();
+        assert!(app.show_instructions);
+        press(&mut app, 'u').unwrap();
+        assert_eq!(app.theme, Theme::DARK);
+        press(&mut app, 'u').unwrap();
+        assert_eq!((app.tasks[0].color_index, app.selected), (1, 1));
+        press(&mut app, 'u').unwrap();
+        assert_eq!(app.tasks[0].color_index, 0);
+        press(&mut app, 'u').unwrap();
+        assert!(app.status.contains("Nothing to undo"));
+
+        for _ in 0..MAX_UNDO + 5 {
+            press(&mut app, 'b').unwrap();
+        }
+        assert_eq!(app.undo_stack.len(), MAX_UNDO);
+    }
+
+    #[test]
+    fn source_errors_show_in_a_red_banner_until_dismissed() {
+        let mut app = ProgressApp::new(["Download"]);
+        app.handle_event(Event::Error("Can't open build.fifo: denied".into()))
+            .unwrap();
+        let buffer = render(&app);
+        assert!(row(&buffer, 3).contains("Error"));
+        assert!(row(&buffer, 4).contains("Can't open build.fifo: denied"));
+        assert!(row(&buffer, 5).contains("x to dismiss"));
+        assert_eq!(buffer[(2, 4)].fg, app.theme.gauge[4]);
+        assert!(app.messages.back().unwrap().contains("build.fifo"));
+
+        app.handle_key(KeyEvent::from(KeyCode::Char('x'))).unwrap();
+        assert_eq!(app.error, None);
+        assert!(!row(&render(&app), 4).contains("build.fifo"));
+    }
+
+    #[test]
+    fn smooth_fill_draws_partial_cells_without_moving_the_label() {
+        let mut app = ProgressApp {
+            tasks: vec![Task::new("Download")],
+            ..Default::default()
+        };
+        app.update_progress(0, 0.1);
+        let blocky = row(&render(&app), 5);
+        app.smooth = true;
+        let smooth = row(&render(&app), 5);
+
+        let partial = ['▏', '▎', '▍', '▌', '▋', '▊', '▉'];
+        assert!(!blocky.contains(partial));
+        assert!(smooth.contains(partial));
+        assert_eq!(blocky.find("10%"), smooth.find("10%"));
+    }
+
+    #[test]
+    fn eased_gauges_converge_exactly_on_the_true_progress() {
+        let mut app = ProgressApp {
+            tasks: vec![Task::new("stdin")],
+            ease: Some(0.5),
+            ..Default::default()
+        };
+        app.update_progress(0, 0.5);
+        assert_eq!(app.tasks[0].displayed, 0.0);
+        assert!(row(&render(&app), 5).contains(" 50% ("));
+
+        app.ease_displayed_progress();
+        assert_eq!(app.tasks[0].displayed, 0.25);
+        for _ in 0..20 {
+            app.ease_displayed_progress();
+        }
+        assert_eq!(app.tasks[0].displayed, 0.5);
+    }
+
+    #[test]
+    fn averaged_gauges_steady_jitter_but_finish_on_the_true_value() {
+        let mut app = ProgressApp {
+            tasks: vec![Task::new("stdin")],
+            average_window: Some(4),
+            ..Default::default()
+        };
+        for progress in [0.2, 0.6, 0.2, 0.6, 0.4] {
+            app.update_progress(0, progress);
+        }
+        // The oldest 0.2 has dropped out of the window.
+        assert!((app.tasks[0].displayed - 0.45).abs() < 1e-9);
+        assert!(row(&render(&app), 5).contains("40% ("));
+
+        app.update_progress(0, 1.0);
+        assert!(app.tasks[0].completed);
+        assert_eq!(app.tasks[0].displayed, 1.0);
+    }
+
+    #[test]
+    fn duration_mode_fills_from_elapsed_time_on_each_tick() {
+        let mut app = ProgressApp {
+            tasks: vec![Task::new("Timer")],
+            duration: Some(Duration::from_secs(60)),
+            ..Default::default()
+        };
+        app.tasks[0].start -= Duration::from_secs(15);
+        app.advance_timers();
+        assert!((app.tasks[0].progress - 0.25).abs() < 0.01);
+        assert!(row(&render(&app), 5).contains("25% • 45s left"));
+
+        app.tasks[0].start -= Duration::from_secs(60);
+        app.advance_timers();
+        assert_eq!(app.tasks[0].progress, 1.0);
+        assert!(app.tasks[0].completed);
+    }
+
+    #[test]
+    fn title_counts_completed_tasks_and_can_drop_the_emoji() {
+        let mut app = ProgressApp {
+            tasks: vec![Task::new("Download"), Task::new("Test")],
+            no_emoji: true,
+            ..Default::default()
+        };
+        app.update_progress(1, 1.0);
+
+        let title = row(&render(&app), 0);
+        assert_eq!(title.trim(), "[*] Process Overview — 1/2 complete");
+    }
+
+    #[test]
+    fn clock_sits_right_of_the_title_until_space_runs_out() {
+        let app = ProgressApp {
+            show_clock: true,
+            no_emoji: true,
+            ..ProgressApp::new(["Download"])
+        };
+        let title = row(&render(&app), 0);
+        let time =
+            regex::Regex::new(r"^ {24}\[\*\] Process Overview — Download {16}\d\d:\d\d:\d\d $");
+        assert!(time.unwrap().is_match(&title), "{title}");
+
+        let mut terminal = Terminal::new(TestBackend::new(50, 20)).unwrap();
+        terminal.draw(|f| app.draw(f)).unwrap();
+        let title = row(terminal.backend().buffer(), 0);
+        assert_eq!(title.trim(), "[*] Process Overview — Download");
+    }
+
+    #[test]
+    fn ctrl_c_quits_without_toggling_the_color() {
+        let mut app = ProgressApp {
+            tasks: vec![Task::new("Download")],
+            ..Default::default()
+        };
+        let ctrl = |c| KeyEvent::new(KeyCode::Char(c), KeyModifiers::CONTROL);
+
+        app.handle_key(ctrl('t')).unwrap();
+        assert_eq!(app.theme, Theme::DARK);
+        app.handle_key(KeyEvent::from(KeyCode::Char('c'))).unwrap();
+        assert_eq!(app.tasks[0].color_index, 1);
+
+        app.handle_key(ctrl('c')).unwrap();
+        assert!(app.exit);
+        assert_eq!(app.tasks[0].color_index, 1);
+    }
+
+    #[test]
+    fn run_skips_redraws_while_the_terminal_is_unfocused() {
+        let mut terminal = Terminal::new(TestBackend::new(80, 20)).unwrap();
+        let mut app = ProgressApp {
+            tasks: vec![Task::new("Download")],
+            focused: false,
+            ..Default::default()
+        };
+        let (tx, rx) = mpsc::channel();
+        tx.send(Event::Progress(0, 0.5)).unwrap();
+        drop(tx);
+
+        app.run(&mut terminal, rx).unwrap();
+        assert_eq!(app.tasks[0].progress, 0.5);
+        assert!(app.last_draw.is_none());
+        assert_eq!(
+            *terminal.backend().buffer(),
+            Buffer::empty(Rect::new(0, 0, 80, 20))
+        );
+
+        let (tx, rx) = mpsc::channel();
+        tx.send(Event::FocusGained).unwrap();
+        drop(tx);
+        app.exit = false;
+        app.run(&mut terminal, rx).unwrap();
+        assert!(app.focused);
+    }
+
+    #[test]
+    fn clicking_a_button_performs_its_action() {
+        let mut terminal = Terminal::new(TestBackend::new(80, 20)).unwrap();
+        let mut app = ProgressApp {
+            tasks: vec![Task::new("Download")],
+            ..Default::default()
+        };
+        terminal.draw(|f| app.draw(f)).unwrap();
+
+        let (button, action) = app.buttons.borrow()[0];
+        assert_eq!(action, Action::ToggleColor);
+        app.handle_mouse(crossterm::event::MouseEvent {
+            kind: MouseEventKind::Down(MouseButton::Left),
+            column: button.x,
+            row: button.y,
+            modifiers: crossterm::event::KeyModifiers::NONE,
+        });
+        assert_eq!(app.tasks[0].color_index, 1);
+    }
+
+    #[test]
+    fn per_task_actions_follow_the_wrapping_selection() {
+        let (mut app, commands) = with_workers(ProgressApp::new(["Download", "Test"]));
+        app.move_selection(-1);
+        assert_eq!(app.selected, 1);
+        app.perform(Action::TogglePause);
+        app.perform(Action::ToggleColor);
+        app.perform(Action::SpeedUp);
+        assert!(!app.tasks[0].paused && app.tasks[1].paused);
+        assert_eq!(
+            commands.try_iter().collect::<Vec<_>>(),
+            [WorkerCommand::Stop(1), WorkerCommand::SetSpeed(1.25)]
+        );
+        assert_eq!((app.tasks[0].color_index, app.tasks[1].color_index), (0, 1));
+
+        app.move_selection(1);
+        assert_eq!(app.selected, 0);
+    }
+
+    #[test]
+    fn selection_scrolls_the_task_list_to_stay_visible() {
+        let mut app = ProgressApp {
+            tasks: (0..5).map(|i| Task::new(&format!("Task {i}"))).collect(),
+            ..Default::default()
+        };
+        app.visible_tasks.set(2);
+
+        app.move_selection(1);
+        assert_eq!((app.selected, app.scroll_offset), (1, 0));
+        app.move_selection(1);
+        assert_eq!((app.selected, app.scroll_offset), (2, 1));
+        app.move_page(1);
+        assert_eq!((app.selected, app.scroll_offset), (4, 3));
+        app.move_page(1);
+        assert_eq!((app.selected, app.scroll_offset), (4, 3));
+        app.move_selection(1);
+        assert_eq!((app.selected, app.scroll_offset), (0, 0));
+        app.move_page(-1);
+        assert_eq!((app.selected, app.scroll_offset), (0, 0));
+    }
+
+    #[test]
+    fn snapshots_keep_the_layout_as_plain_text() {
+        let mut buffer = Buffer::empty(Rect::new(0, 0, 8, 3));
+        buffer.set_string(1, 0, "日本 ok", Style::default().fg(Color::Red));
+        buffer.set_string(0, 2, "│ end", Style::default());
+        assert_eq!(buffer_to_text(&buffer), " 日本 ok\n\n│ end\n");
+
+        let app = ProgressApp::new(["Download"]);
+        let screen = buffer_to_text(&render(&app));
+        assert_eq!(screen.lines().count(), 20);
+        assert!(screen.lines().nth(4).unwrap().contains("Download"));
+    }
+
+    #[test]
+    fn oneshot_frames_keep_their_colors_as_ansi_escapes() {
+        let mut buffer = Buffer::empty(Rect::new(0, 0, 6, 2));
+        let bold_red = Style::default().fg(Color::Red).add_modifier(Modifier::BOLD);
+        buffer.set_string(0, 0, "ab", bold_red);
+        buffer.set_string(1, 1, "日", Style::default().bg(Color::Blue));
+        assert_eq!(
+            buffer_to_ansi(&buffer),
+            "\x1b[0m\x1b[38;5;1m\x1b[1mab\x1b[0m\n \x1b[0m\x1b[48;5;4m日\x1b[0m\n"
+        );
+
+        // Without the escapes it is the same frame a snapshot saves, bar the
+        // painted blanks ending the button row.
+        let buffer = render(&ProgressApp::new(["Download"]));
+        let escapes = regex::Regex::new("\x1b\\[[0-9;]*m").unwrap();
+        let ansi = escapes
+            .replace_all(&buffer_to_ansi(&buffer), "")
+            .into_owned();
+        assert!(
+            ansi.lines()
+                .map(str::trim_end)
+                .eq(buffer_to_text(&buffer).lines())
+        );
+    }
+
+    #[test]
+    fn marquee_bounces_between_the_edges() {
+        let area = Rect::new(1, 0, 10, 1);
+        let xs: Vec<u16> = (0..6).map(|frame| marquee(area, frame).x).collect();
+        assert_eq!(xs, [1, 3, 5, 7, 9, 7]);
+        assert_eq!(marquee(area, 0).width, 2);
+        assert!(marquee(area, usize::MAX).right() <= area.right());
+    }
+
+    #[test]
+    fn rate_is_the_sampled_slope_and_zero_when_stalled() {
+        let mut task = Task::new("Download");
+        assert_eq!(task.rate(), 0.0);
+
+        let now = Instant::now();
+        task.samples.push_back((now - Duration::from_secs(2), 0.1));
+        task.samples.push_back((now, 0.2));
+        task.progress = 0.2;
+        assert!((task.rate() - 0.05).abs() < 1e-9);
+        assert_eq!(task.eta().map(|eta| eta.as_secs()), Some(16));
+
+        task.samples
+            .iter_mut()
+            .for_each(|(at, _)| *at -= RATE_TIMEOUT);
+        assert_eq!(task.rate(), 0.0);
+        assert_eq!(task.eta(), None);
+    }
+
+    #[test]
+    fn wider_eta_windows_steady_the_estimate_for_bursty_progress() {
+        // 4% arrives in a burst every fifth update, with updates 10ms apart.
+        let start = Instant::now() - Duration::from_secs(1);
+        let etas = |window: usize| {
+            let mut task = Task::new("Download");
+            (0..60)
+                .map(|i| {
+                    task.progress = f64::from(i / 5 + 1) * 0.04;
+                    task.record_sample(
+                        start + Duration::from_millis(10 * i as u64),
+                        task.progress,
+                        window,
+                    );
+                    task.eta().map(|eta| eta.as_secs_f64())
+                })
+                .skip(40)
+                .collect::<Vec<_>>()
+        };
+        // How far the estimate swings, as infinite when it drops out.
+        let swing = |etas: Vec<Option<f64>>| {
+            let etas: Option<Vec<f64>> = etas.into_iter().collect();
+            etas.map_or(f64::INFINITY, |etas| {
+                etas.iter().copied().fold(f64::MIN, f64::max)
+                    - etas.iter().copied().fold(f64::MAX, f64::min)
+            })
+        };
+        let (narrow, wide) = (swing(etas(5)), swing(etas(40)));
+        assert!(wide.is_finite());
+        assert!(wide < narrow, "{wide} vs {narrow}");
+    }
+
+    #[test]
+    fn workers_follow_commands_and_stop_once_the_ui_is_gone() {
+        let (tx, rx) = mpsc::channel();
+        let (commands, command_rx) = mpsc::channel();
+        let worker = thread::spawn(move || {
+            run_background_thread(tx, command_rx, vec![(0.1, 0.0), (0.1, 0.5)], WORKER_TICK)
+        });
+        for command in [
+            WorkerCommand::Stop(1),
+            WorkerCommand::JumpTo(0, 0.8),
+            WorkerCommand::SetSpeed(2.0),
+        ] {
+            commands.send(command).unwrap();
+        }
+        // Commands are handled before the first tick, so only the running
+        // task reports, from where it was moved to.
+        match rx.recv().unwrap() {
+            Event::Progress(index, progress) => {
+                assert_eq!(index, 0);
+                assert!((progress - 1.0).abs() < 1e-9);
+            }
+            _ => panic!("expected a progress event"),
+        }
+
+        // Hanging up stops the thread even while every task is paused.
+        commands.send(WorkerCommand::Stop(0)).unwrap();
+        drop(commands);
+        worker.join().unwrap();
+
+        // A running worker also stops once nobody listens to its progress.
+        let (tx, rx) = mpsc::channel();
+        drop(rx);
+        let (_commands, command_rx) = mpsc::channel();
+        run_background_thread(tx, command_rx, vec![(0.1, 0.0)], WORKER_TICK);
+    }
+
+    #[test]
+    fn overall_progress_is_the_mean_and_zero_without_tasks() {
+        assert_eq!(ProgressApp::default().overall_progress(), 0.0);
+
+        let mut app = ProgressApp {
+            tasks: vec![Task::new("Download"), Task::new("Test")],
+            ..Default::default()
+        };
+        app.update_progress(0, 1.0);
+        app.update_progress(1, 0.5);
+        assert_eq!(app.overall_progress(), 0.75);
+        assert_eq!(app.history.back(), Some(&75));
+
+        app.tasks[0].weight = 3.0;
+        assert_eq!(app.overall_progress(), 0.875);
+        app.tasks.iter_mut().for_each(|task| task.weight = 0.0);
+        assert_eq!(app.overall_progress(), 0.75);
+    }
+
+    #[test]
+    fn state_round_trips_and_ignores_corrupt_files() {
+        let path = env::temp_dir().join(format!("progress-tracker-{}.json", process::id()));
+        let mut app = ProgressApp {
+            tasks: vec![Task::new("Download"), Task::new("Test")],
+            ..Default::default()
+        };
+        app.tasks[0].progress = 0.4;
+        app.tasks[0].color_index = 2;
+        app.save_state(&path, &[]).unwrap();
+
+        let mut restored = ProgressApp {
+            tasks: vec![Task::new("Download"), Task::new("Other")],
+            ..Default::default()
+        };
+        restored.load_state(&path, &[]);
+        assert_eq!(restored.tasks[0].color_index, 2);
+        assert_eq!(restored.tasks[0].progress, 0.4);
+        assert_eq!(restored.tasks[1].progress, 0.0);
+
+        fs::write(&path, "{ not json").unwrap();
+        let mut fallback = ProgressApp {
+            tasks: vec![Task::new("Download")],
+            ..Default::default()
+        };
+        fallback.load_state(&path, &[]);
+        assert_eq!(fallback.tasks[0].progress, 0.0);
+        let _ = fs::remove_file(&path);
+    }
+
+    #[test]
+    fn commands_race_their_last_unfinished_run_as_a_dim_baseline() {
+        let path = env::temp_dir().join(format!("progress-baseline-{}.json", process::id()));
+        let command = ["make".to_string(), "all".to_string()];
+        let mut app = ProgressApp {
+            tasks: vec![Task::new("make")],
+            ..Default::default()
+        };
+        app.tasks[0].progress = 0.9;
+        app.save_state(&path, &command).unwrap();
+
+        // Neither the demo nor another command picks it up.
+        let mut other = ProgressApp::new(["make"]);
+        other.load_state(&path, &[]);
+        other.load_state(&path, &["make".to_string()]);
+        assert_eq!(
+            (other.tasks[0].progress, other.tasks[0].baseline),
+            (0.0, None)
+        );
+
+        let mut rerun = ProgressApp::new(["make"]);
+        rerun.load_state(&path, &command);
+        assert_eq!(
+            (rerun.tasks[0].progress, rerun.tasks[0].baseline),
+            (0.0, Some(0.9))
+        );
+        rerun.update_progress(0, 0.25);
+        let buffer = render(&rerun);
+        // The gauge's inner 76 columns start at x = 2: 19 filled, then the
+        // previous run shaded around the label up to 68.
+        assert_eq!(buffer[(20, 5)].symbol(), "█");
+        assert_eq!(buffer[(21, 5)].symbol(), "▒");
+        assert!(buffer[(21, 5)].modifier.contains(Modifier::DIM));
+        assert!(row(&buffer, 5).contains("25% (0.0s) • "));
+        assert_eq!(buffer[(69, 5)].symbol(), "▒");
+        assert_eq!(buffer[(70, 5)].symbol(), " ");
+
+        // A run that finished leaves nothing to race.
+        rerun.update_progress(0, 1.0);
+        rerun.save_state(&path, &command).unwrap();
+        let mut next = ProgressApp::new(["make"]);
+        next.load_state(&path, &command);
+        assert_eq!(next.tasks[0].baseline, None);
+        let _ = fs::remove_file(&path);
+    }
+
+    #[test]
+    fn read_progress_tags_updates_with_the_task_index() {
+        let (tx, rx) = mpsc::channel();
+        let parser = parser::PercentParser;
+        read_progress(io::Cursor::new("0.2\njunk\n50%\n"), &parser, 2, tx);
+        let updates: Vec<_> = rx
+            .iter()
+            .map(|event| match event {
+                Event::Progress(index, progress) => (index, progress),
+                _ => panic!("expected only progress events"),
+            })
+            .collect();
+        assert_eq!(updates, [(2, 0.2), (2, 0.5), (2, 1.0)]);
+
+        let (tx, rx) = mpsc::channel();
+        let parser = parser::FractionParser::default();
+        read_progress(io::Cursor::new("file 3/4\n"), &parser, 0, tx);
+        let mut app = ProgressApp::new(["Copy"]);
+        app.handle_event(rx.recv().unwrap()).unwrap();
+        app.handle_event(rx.recv().unwrap()).unwrap();
+        assert_eq!(app.tasks[0].count, Some((3, 4)));
+        assert_eq!(app.tasks[0].progress, 0.75);
+    }
+
+    #[test]
+    fn watch_progress_reports_changes_once_the_file_appears() {
+        let path = std::env::temp_dir().join(format!("watch-{}", std::process::id()));
+        let _ = fs::remove_file(&path);
+        let (tx, rx) = mpsc::channel();
+        thread::spawn({
+            let path = path.clone();
+            move || watch_progress(&path, Duration::from_millis(5), 0, tx)
+        });
+
+        thread::sleep(Duration::from_millis(20));
+        fs::write(&path, "42\n").unwrap();
+        assert!(matches!(rx.recv().unwrap(), Event::Progress(0, 0.42)));
+        fs::write(&path, "not a number").unwrap();
+        thread::sleep(Duration::from_millis(20));
+        fs::write(&path, "60%").unwrap();
+        assert!(matches!(rx.recv().unwrap(), Event::Progress(0, 0.6)));
+        fs::remove_file(&path).unwrap();
+        assert!(rx.recv_timeout(Duration::from_millis(20)).is_err());
+    }
+}
@@ -0,0 +1,169 @@
+//! Accepts progress from remote workers over a socket, for `--listen`.
+
+use std::{
+    io::{self, BufRead, BufReader, Read},
+    net::TcpListener,
+    sync::{Arc, mpsc},
+    thread,
+};
+
+use crate::{
+    Event,
+    parser::{self, ProgressParser},
+};
+
+/// Listens on `addr`, a TCP `host:port` or `unix:PATH`, and reports the
+/// progress each connected worker sends for the task of the same name in
+/// `workers`.
+///
+/// Every connection is read on its own thread, one `NAME progress: 0.73`
+/// line per update with the value read by `parser`. A worker hanging up
+/// leaves its task where it was, so it may reconnect and carry on.
+pub fn spawn(
+    addr: &str,
+    workers: Vec<String>,
+    parser: Arc<dyn ProgressParser>,
+    tx: mpsc::Sender<Event>,
+) -> io::Result<()> {
+    let workers: Arc<[String]> = workers.into();
+    if let Some(path) = addr.strip_prefix("unix:") {
+        #[cfg(unix)]
+        {
+            let listener = bind_unix(path)?;
+            thread::spawn(move || accept(listener.incoming(), workers, parser, tx));
+            return Ok(());
+        }
+        #[cfg(not(unix))]
+        return Err(io::Error::new(
+            io::ErrorKind::Unsupported,
+            format!("can't listen on {path}: Unix sockets aren't supported here"),
+        ));
+    }
+    let listener = TcpListener::bind(addr)?;
+    thread::spawn(move || accept(listener.incoming(), workers, parser, tx));
+    Ok(())
+}
+
+/// Binds a Unix socket at `path`, first clearing away a socket file left
+/// behind by an earlier run that nobody is listening on any more.
+#[cfg(unix)]
+fn bind_unix(path: &str) -> io::Result<std::os::unix::net::UnixListener> {
+    use std::os::unix::net::{UnixListener, UnixStream};
+
+    match UnixListener::bind(path) {
+        Err(err)
+            if err.kind() == io::ErrorKind::AddrInUse && UnixStream::connect(path).is_err() =>
+        {
+            std::fs::remove_file(path)?;
+            UnixListener::bind(path)
+        }
+        bound => bound,
+    }
+}
+
+fn accept<S: Read + Send + 'static>(
+    incoming: impl Iterator<Item = io::Result<S>>,
+    workers: Arc<[String]>,
+    parser: Arc<dyn ProgressParser>,
+    tx: mpsc::Sender<Event>,
+) {
+    // A failed handshake only loses that one connection. Each one is a
+    // source of its own, so `--merge` can tell workers sharing a name apart.
+    for (source, stream) in incoming.flatten().enumerate() {
+        let (workers, parser, tx) = (Arc::clone(&workers), Arc::clone(&parser), tx.clone());
+        thread::spawn(move || {
+            read_updates(
+                BufReader::new(stream),
+                source,
+                &workers,
+                parser.as_ref(),
+                &tx,
+            )
+        });
+    }
+}
+
+/// Reports every update on one connection, numbered `source`, until the
+/// worker hangs up or the UI goes away. Lines naming no known worker are
+/// skipped.
+fn read_updates(
+    reader: impl BufRead,
+    source: usize,
+    workers: &[String],
+    parser: &dyn ProgressParser,
+    tx: &mpsc::Sender<Event>,
+) {
+    for line in reader.lines() {
+        let Ok(line) = line else { return };
+        let Some((index, value)) = parse_update(&line, workers) else {
+            continue;
+        };
+        if parser::report(parser, value, index, Some(source), tx).is_err() {
+            return;
+        }
+    }
+}
+
+/// Splits `build progress: 0.73` into the index of the worker named `build`,
+/// ignoring case, and the value `0.73`. The `progress:` is optional.
+fn parse_update<'a>(line: &'a str, workers: &[String]) -> Option<(usize, &'a str)> {
+    let (name, value) = line.trim().split_once(char::is_whitespace)?;
+    let index = workers
+        .iter()
+        .position(|worker| worker.eq_ignore_ascii_case(name))?;
+    let value = value.trim_start();
+    Some((
+        index,
+        value.strip_prefix("progress:").unwrap_or(value).trim(),
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{io::Write, net::TcpStream};
+
+    use super::*;
+    use crate::parser::PercentParser;
+
+    #[test]
+    fn parse_update_matches_workers_by_name() {
+        let workers = ["build".to_string(), "test".to_string()];
+        assert_eq!(
+            parse_update("build progress: 0.73", &workers),
+            Some((0, "0.73"))
+        );
+        assert_eq!(parse_update("  TEST 40% ", &workers), Some((1, "40%")));
+        assert_eq!(parse_update("deploy progress: 0.5", &workers), None);
+        assert_eq!(parse_update("build", &workers), None);
+    }
+
+    #[test]
+    fn connections_are_read_side_by_side_until_they_hang_up() {
+        let (tx, rx) = mpsc::channel();
+        let workers = vec!["build".to_string(), "test".to_string()];
+        // Find a free port by binding and releasing it, as `spawn` doesn't
+        // say which one port 0 picked.
+        let addr = TcpListener::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap();
+        spawn(&addr.to_string(), workers, Arc::new(PercentParser), tx).unwrap();
+
+        let mut build = TcpStream::connect(addr).unwrap();
+        let mut test = TcpStream::connect(addr).unwrap();
+        writeln!(test, "test progress: 0.25").unwrap();
+        let first = rx.recv().unwrap();
+        writeln!(build, "nobody 0.9\nbuild progress: 0.5").unwrap();
+        let second = rx.recv().unwrap();
+        assert!(matches!(first, Event::SourceProgress(1, 1, 0.25)));
+        assert!(matches!(second, Event::SourceProgress(0, 0, 0.5)));
+
+        // Hanging up reports nothing, and the others carry on.
+        drop(build);
+        writeln!(test, "test progress: 60%").unwrap();
+        assert!(matches!(
+            rx.recv().unwrap(),
+            Event::SourceProgress(1, 1, 0.6)
+        ));
+    }
+}
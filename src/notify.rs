@@ -0,0 +1,43 @@
+//! Best-effort desktop notifications via the platform's command-line tool.
+
+use std::{
+    io,
+    process::{Command, Stdio},
+    thread,
+};
+
+/// Shows a desktop notification without waiting for it to be delivered.
+pub fn send(summary: &str, body: &str) -> io::Result<()> {
+    let mut command = if cfg!(target_os = "macos") {
+        let script = format!(
+            "display notification {} with title {}",
+            apple_script_string(body),
+            apple_script_string(summary)
+        );
+        let mut command = Command::new("osascript");
+        command.args(["-e", &script]);
+        command
+    } else if cfg!(unix) {
+        let mut command = Command::new("notify-send");
+        command.args([summary, body]);
+        command
+    } else {
+        return Err(io::Error::new(
+            io::ErrorKind::Unsupported,
+            "desktop notifications are not supported on this platform",
+        ));
+    };
+
+    let mut child = command
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()?;
+    // Reap the child in the background so it never blocks the UI.
+    thread::spawn(move || child.wait());
+    Ok(())
+}
+
+fn apple_script_string(text: &str) -> String {
+    format!("\"{}\"", text.replace('\\', "\\\\").replace('"', "\\\""))
+}
@@ -0,0 +1,263 @@
+//! Ways of reading progress out of a line of text, chosen with `--parser`.
+
+use std::sync::{Arc, mpsc};
+
+use regex::Regex;
+
+use crate::Event;
+
+/// Matches the last-resort `NN%` / `NN.N%` token tools commonly print.
+pub const DEFAULT_PATTERN: &str = r"(\d+(?:\.\d+)?)%";
+
+/// Turns one line of a tool's output into a progress ratio.
+pub trait ProgressParser: Send + Sync {
+    /// The progress from `0.0` to `1.0` reported by `line`, if it reports any.
+    fn parse(&self, line: &str) -> Option<f64>;
+
+    /// The `(done, total)` counts behind `line`, for parsers that read them.
+    fn count(&self, _line: &str) -> Option<(u64, u64)> {
+        None
+    }
+
+    /// The `(current, total)` byte counts in `line`, for parsers that read
+    /// them; a total of `0` means it isn't known.
+    fn bytes(&self, _line: &str) -> Option<(u64, u64)> {
+        None
+    }
+}
+
+/// The parsers `--parser` can pick between.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ParserKind {
+    Percent,
+    Fraction,
+    Bytes,
+    Regex,
+}
+
+impl ParserKind {
+    const NAMES: [(&str, ParserKind); 4] = [
+        ("percent", ParserKind::Percent),
+        ("fraction", ParserKind::Fraction),
+        ("bytes", ParserKind::Bytes),
+        ("regex", ParserKind::Regex),
+    ];
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::NAMES
+            .iter()
+            .find(|(known, _)| *known == name)
+            .map(|(_, kind)| *kind)
+    }
+
+    pub fn name(self) -> &'static str {
+        Self::NAMES
+            .iter()
+            .find(|(_, kind)| *kind == self)
+            .map_or("", |(name, _)| name)
+    }
+
+    /// Builds the parser, with `pattern` used by the regex one.
+    pub fn build(self, pattern: &Regex) -> Arc<dyn ProgressParser> {
+        match self {
+            ParserKind::Percent => Arc::new(PercentParser),
+            ParserKind::Fraction => Arc::new(FractionParser::default()),
+            ParserKind::Bytes => Arc::new(BytesParser::default()),
+            ParserKind::Regex => Arc::new(RegexParser(pattern.clone())),
+        }
+    }
+}
+
+/// Lines holding nothing but a ratio such as `0.42` or a percentage such as
+/// `42%`.
+pub struct PercentParser;
+
+impl ProgressParser for PercentParser {
+    fn parse(&self, line: &str) -> Option<f64> {
+        let line = line.trim();
+        let progress = match line.strip_suffix('%') {
+            Some(percent) => percent.trim().parse::<f64>().ok()? / 100.0,
+            None => line.parse::<f64>().ok()?,
+        };
+        (0.0..=1.0).contains(&progress).then_some(progress)
+    }
+}
+
+/// The first `done/total` count anywhere in a line, as in `12/40 files`.
+pub struct FractionParser {
+    pattern: Regex,
+}
+
+impl Default for FractionParser {
+    fn default() -> Self {
+        Self {
+            pattern: Regex::new(r"(\d+)\s*/\s*(\d+)").expect("fraction pattern is valid"),
+        }
+    }
+}
+
+impl ProgressParser for FractionParser {
+    fn parse(&self, line: &str) -> Option<f64> {
+        let (done, total) = self.count(line)?;
+        Some(done as f64 / total as f64)
+    }
+
+    fn count(&self, line: &str) -> Option<(u64, u64)> {
+        let captures = self.pattern.captures(line)?;
+        let done: u64 = captures[1].parse().ok()?;
+        let total: u64 = captures[2].parse().ok()?;
+        (total > 0 && done <= total).then_some((done, total))
+    }
+}
+
+/// The first byte count anywhere in a line, as in `45.2 MB / 100 MB`,
+/// `3.1MiB of 20MiB` or a bare `47395635/104857600`.
+pub struct BytesParser {
+    pattern: Regex,
+}
+
+impl Default for BytesParser {
+    fn default() -> Self {
+        Self {
+            pattern: Regex::new(
+                r"(?i)(\d+(?:\.\d+)?)\s*([kmgt]i?b?|b)?\s*(?:/|of)\s*(\d+(?:\.\d+)?)\s*([kmgt]i?b?|b)?\b",
+            )
+            .expect("bytes pattern is valid"),
+        }
+    }
+}
+
+impl ProgressParser for BytesParser {
+    fn parse(&self, line: &str) -> Option<f64> {
+        let (current, total) = self.bytes(line)?;
+        (total > 0 && current <= total).then(|| current as f64 / total as f64)
+    }
+
+    fn bytes(&self, line: &str) -> Option<(u64, u64)> {
+        let captures = self.pattern.captures(line)?;
+        let size = |number: usize, unit: usize| {
+            let number: f64 = captures[number].parse().ok()?;
+            let unit = captures.get(unit).map_or("", |unit| unit.as_str());
+            Some((number * unit_size(unit)?) as u64)
+        };
+        Some((size(1, 2)?, size(3, 4)?))
+    }
+}
+
+/// Bytes in one `unit` such as `MB` (10^6) or `MiB` (2^20); plain `B` or
+/// none at all is a byte, and the trailing `B` may be left off.
+fn unit_size(unit: &str) -> Option<f64> {
+    let unit = unit.to_ascii_lowercase();
+    let unit = unit.strip_suffix('b').unwrap_or(&unit);
+    let (prefix, base) = match unit.strip_suffix('i') {
+        Some(prefix) => (prefix, 1024.0_f64),
+        None => (unit, 1000.0),
+    };
+    let power = match prefix {
+        "" => 0,
+        "k" => 1,
+        "m" => 2,
+        "g" => 3,
+        "t" => 4,
+        _ => return None,
+    };
+    Some(base.powi(power))
+}
+
+/// Lines matching a regex, whose first capture group (or the whole match if
+/// it has none) is read as a percentage.
+pub struct RegexParser(pub Regex);
+
+impl ProgressParser for RegexParser {
+    fn parse(&self, line: &str) -> Option<f64> {
+        let captures = self.0.captures(line)?;
+        let matched = captures.get(1).or_else(|| captures.get(0))?;
+        let percent: f64 = matched.as_str().trim_end_matches('%').trim().parse().ok()?;
+        (0.0..=100.0).contains(&percent).then_some(percent / 100.0)
+    }
+}
+
+/// Sends the progress `line` reports for task `index`, preceded by its counts
+/// when the parser reads them, or just its byte counts, which the UI works
+/// the progress out from. `source` numbers the sender among several feeding
+/// the same task, if there may be more than one. Fails once the receiver has
+/// gone away.
+pub fn report(
+    parser: &dyn ProgressParser,
+    line: &str,
+    index: usize,
+    source: Option<usize>,
+    tx: &mpsc::Sender<Event>,
+) -> Result<(), mpsc::SendError<Event>> {
+    if let Some((current, total)) = parser.bytes(line) {
+        return tx.send(Event::Bytes(index, current, total));
+    }
+    let Some(progress) = parser.parse(line) else {
+        return Ok(());
+    };
+    if let Some((done, total)) = parser.count(line) {
+        tx.send(Event::Count(index, done, total))?;
+    }
+    tx.send(match source {
+        Some(source) => Event::SourceProgress(index, source, progress),
+        None => Event::Progress(index, progress),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn percent_parser_accepts_ratios_and_percentages() {
+        assert_eq!(PercentParser.parse("0.25"), Some(0.25));
+        assert_eq!(PercentParser.parse(" 40% "), Some(0.4));
+        assert_eq!(PercentParser.parse("1"), Some(1.0));
+        assert_eq!(PercentParser.parse("150%"), None);
+        assert_eq!(PercentParser.parse("-0.1"), None);
+        assert_eq!(PercentParser.parse("building..."), None);
+        assert_eq!(PercentParser.count("40%"), None);
+    }
+
+    #[test]
+    fn fraction_parser_reads_counts_anywhere_in_the_line() {
+        let parser = FractionParser::default();
+        assert_eq!(parser.parse("copied 12/40 files"), Some(0.3));
+        assert_eq!(parser.count("copied 12 / 40 files"), Some((12, 40)));
+        assert_eq!(parser.parse("40/40"), Some(1.0));
+        assert_eq!(parser.parse("5/0"), None);
+        assert_eq!(parser.parse("41/40"), None);
+        assert_eq!(parser.parse("42%"), None);
+    }
+
+    #[test]
+    fn bytes_parser_reads_sizes_with_and_without_units() {
+        let parser = BytesParser::default();
+        assert_eq!(
+            parser.bytes("45.2 MB / 100 MB"),
+            Some((45_200_000, 100_000_000))
+        );
+        assert_eq!(parser.bytes("got 3MiB of 4MiB"), Some((3 << 20, 4 << 20)));
+        assert_eq!(parser.bytes("512k/2M"), Some((512_000, 2_000_000)));
+        assert_eq!(
+            parser.bytes("47395635/104857600"),
+            Some((47_395_635, 104_857_600))
+        );
+        assert_eq!(parser.parse("50 B / 200 B"), Some(0.25));
+        assert_eq!(parser.bytes("5 MB / 0 B"), Some((5_000_000, 0)));
+        assert_eq!(parser.parse("5 MB / 0 B"), None);
+        assert_eq!(parser.bytes("42%"), None);
+    }
+
+    #[test]
+    fn regex_parser_reads_the_percentage_capture() {
+        let default = RegexParser(Regex::new(DEFAULT_PATTERN).unwrap());
+        assert_eq!(default.parse("copying files  42%"), Some(0.42));
+        assert_eq!(default.parse("[=====>    ] 12.5% eta 3s"), Some(0.125));
+        assert_eq!(default.parse("no progress here"), None);
+        assert_eq!(default.parse("500%"), None);
+
+        let custom = RegexParser(Regex::new(r"step (\d+) of 100").unwrap());
+        assert_eq!(custom.parse("step 7 of 100"), Some(0.07));
+    }
+}
@@ -0,0 +1,127 @@
+//! Appends progress to the `--log-file`, for a record that outlasts the UI.
+
+use std::{
+    fs::{File, OpenOptions},
+    io::{self, BufWriter, Write},
+    path::{Path, PathBuf},
+    time::{Duration, Instant},
+};
+
+/// Shortest time between two lines for the same task, and between flushes.
+pub const LOG_INTERVAL: Duration = Duration::from_secs(1);
+
+/// An open log file, written one line per change to a task's progress: the
+/// time, such as `2024-05-01 12:00:03`, the task's name and its percentage,
+/// separated by tabs.
+pub struct ProgressLog {
+    path: PathBuf,
+    writer: BufWriter<File>,
+    /// When each task, by index, last got a line and the progress it showed.
+    logged: Vec<Option<(Instant, f64)>>,
+    /// Lines are waiting in the buffer.
+    unflushed: bool,
+    flushed_at: Instant,
+}
+
+impl ProgressLog {
+    /// Opens `path` for appending, creating it if needed.
+    pub fn open(path: &Path) -> io::Result<Self> {
+        let file = OpenOptions::new().create(true).append(true).open(path)?;
+        Ok(Self {
+            path: path.to_path_buf(),
+            writer: BufWriter::new(file),
+            logged: Vec::new(),
+            unflushed: false,
+            flushed_at: Instant::now(),
+        })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Appends a line for task `index` if its progress changed since its last
+    /// line, and that was at least `LOG_INTERVAL` ago unless `force` is set.
+    pub fn record(
+        &mut self,
+        index: usize,
+        name: &str,
+        progress: f64,
+        now: Instant,
+        force: bool,
+    ) -> io::Result<()> {
+        if self.logged.len() <= index {
+            self.logged.resize(index + 1, None);
+        }
+        if let Some((at, logged)) = self.logged[index]
+            && (logged == progress || !force && now.duration_since(at) < LOG_INTERVAL)
+        {
+            return Ok(());
+        }
+        writeln!(
+            self.writer,
+            "{}\t{name}\t{:.1}%",
+            chrono::Local::now().format("%Y-%m-%d %H:%M:%S"),
+            progress * 100.0
+        )?;
+        self.logged[index] = Some((now, progress));
+        self.unflushed = true;
+        Ok(())
+    }
+
+    /// Writes buffered lines to the file if the last flush was at least
+    /// `LOG_INTERVAL` ago, or straight away with `force`.
+    pub fn flush(&mut self, now: Instant, force: bool) -> io::Result<()> {
+        if !self.unflushed || !force && now.duration_since(self.flushed_at) < LOG_INTERVAL {
+            return Ok(());
+        }
+        self.writer.flush()?;
+        self.unflushed = false;
+        self.flushed_at = now;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use super::*;
+
+    #[test]
+    fn record_throttles_each_task_and_skips_unchanged_progress() {
+        let path = std::env::temp_dir().join(format!("progress-log-{}.txt", std::process::id()));
+        fs::write(&path, "earlier run\n").unwrap();
+        let mut log = ProgressLog::open(&path).unwrap();
+        let start = Instant::now();
+        log.record(0, "Download", 0.25, start, false).unwrap();
+        log.record(1, "Upload", 0.5, start, false).unwrap();
+        // Too soon after the last line, then no change at all.
+        log.record(0, "Download", 0.3, start + LOG_INTERVAL / 2, false)
+            .unwrap();
+        log.record(1, "Upload", 0.5, start + LOG_INTERVAL, false)
+            .unwrap();
+        log.record(0, "Download", 0.4, start + LOG_INTERVAL, false)
+            .unwrap();
+        log.record(0, "Download", 1.0, start + LOG_INTERVAL, true)
+            .unwrap();
+        log.flush(start, true).unwrap();
+
+        let contents = fs::read_to_string(&path).unwrap();
+        let lines: Vec<_> = contents
+            .lines()
+            .map(|line| line.split_once('\t').map_or(line, |(_, rest)| rest))
+            .collect();
+        assert_eq!(
+            lines,
+            [
+                "earlier run",
+                "Download\t25.0%",
+                "Upload\t50.0%",
+                "Download\t40.0%",
+                "Download\t100.0%",
+            ]
+        );
+        fs::remove_file(&path).unwrap();
+    }
+}
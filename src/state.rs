@@ -0,0 +1,29 @@
+//! Progress saved on exit so the next run of the demo can pick up from it,
+//! or the next run of a tracked command can be compared against it.
+
+use std::{env, path::PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Default, Deserialize, Serialize)]
+pub struct SavedState {
+    /// The tracked command the progress belongs to, empty for the demo.
+    #[serde(default)]
+    pub command: Vec<String>,
+    pub tasks: Vec<SavedTask>,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct SavedTask {
+    pub name: String,
+    pub progress: f64,
+    #[serde(default)]
+    pub color_index: usize,
+}
+
+/// `~/.progress-tracker.json`, or `None` if there is no home directory.
+pub fn default_path() -> Option<PathBuf> {
+    env::var_os("HOME")
+        .or_else(|| env::var_os("USERPROFILE"))
+        .map(|home| PathBuf::from(home).join(".progress-tracker.json"))
+}
@@ -0,0 +1,140 @@
+//! Color presets for dark and light terminal backgrounds.
+
+use std::str::FromStr;
+
+use ratatui::style::Color;
+
+use crate::PALETTE;
+
+/// The colors used throughout the UI, toggled between presets with `t`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Theme {
+    pub name: &'static str,
+    pub title: Color,
+    pub border: Color,
+    /// Highlights key names in the footer and help popup.
+    pub accent: Color,
+    /// Gauge colors cycled by `c`, in the same order as `PALETTE`.
+    pub gauge: [Color; 5],
+    pub complete: Color,
+    /// Borders of tasks that have stalled.
+    pub warning: Color,
+}
+
+impl Theme {
+    pub const DARK: Self = Self {
+        name: "dark",
+        title: Color::Reset,
+        border: Color::Reset,
+        accent: Color::Blue,
+        gauge: PALETTE,
+        complete: Color::LightGreen,
+        warning: Color::Yellow,
+    };
+
+    /// Darker shades that stay readable on a white background.
+    pub const LIGHT: Self = Self {
+        name: "light",
+        title: Color::Black,
+        border: Color::DarkGray,
+        accent: Color::Blue,
+        gauge: [
+            Color::Rgb(0, 135, 0),
+            Color::Rgb(175, 135, 0),
+            Color::Rgb(0, 135, 135),
+            Color::Rgb(135, 0, 135),
+            Color::Rgb(175, 0, 0),
+        ],
+        complete: Color::Rgb(0, 95, 0),
+        warning: Color::Rgb(175, 95, 0),
+    };
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        [Self::DARK, Self::LIGHT]
+            .into_iter()
+            .find(|theme| theme.name == name)
+    }
+
+    pub fn toggled(self) -> Self {
+        if self == Self::DARK {
+            Self::LIGHT
+        } else {
+            Self::DARK
+        }
+    }
+
+    /// Health color for a progress ratio: red below a third, yellow below two
+    /// thirds, green above.
+    pub fn threshold_color(&self, progress: f64) -> Color {
+        if progress < 0.33 {
+            self.gauge[4]
+        } else if progress < 0.66 {
+            self.gauge[1]
+        } else {
+            self.gauge[0]
+        }
+    }
+}
+
+/// Parses a color given on the command line or in a config file: one of the
+/// sixteen ANSI names such as `red` or `lightblue`, a 256-color index such as
+/// `214`, or an RGB value written `#ff8000` or `rgb(255, 128, 0)`.
+pub fn parse_color(value: &str) -> Result<Color, String> {
+    let unknown = || {
+        format!(
+            "unknown color '{value}', expected a name, an index up to 255, #rrggbb or rgb(r, g, b)"
+        )
+    };
+    let trimmed = value.trim();
+    if let Some(channels) = trimmed
+        .strip_prefix("rgb(")
+        .and_then(|rest| rest.strip_suffix(')'))
+    {
+        let channels = channels
+            .split(',')
+            .map(|channel| channel.trim().parse::<u8>())
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|_| unknown())?;
+        let [r, g, b] = channels[..] else {
+            return Err(unknown());
+        };
+        return Ok(Color::Rgb(r, g, b));
+    }
+    Color::from_str(trimmed).map_err(|_| unknown())
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Self::DARK
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_color_reads_names_indexes_and_rgb() {
+        assert_eq!(parse_color("cyan"), Ok(Color::Cyan));
+        assert_eq!(parse_color("LightBlue"), Ok(Color::LightBlue));
+        assert_eq!(parse_color("214"), Ok(Color::Indexed(214)));
+        assert_eq!(parse_color("#ff8000"), Ok(Color::Rgb(255, 128, 0)));
+        assert_eq!(parse_color("rgb(255,128,0)"), Ok(Color::Rgb(255, 128, 0)));
+        assert_eq!(
+            parse_color(" rgb( 0, 64 , 255 ) "),
+            Ok(Color::Rgb(0, 64, 255))
+        );
+
+        for invalid in [
+            "blurple",
+            "256",
+            "#ff80",
+            "rgb(255,128)",
+            "rgb(1,2,3,4)",
+            "rgb(300,0,0)",
+            "",
+        ] {
+            assert!(parse_color(invalid).is_err(), "{invalid}");
+        }
+    }
+}
@@ -0,0 +1,115 @@
+//! Streams every progress update to the `--csv` file, so a run's progress
+//! curve can be plotted elsewhere.
+
+use std::{
+    borrow::Cow,
+    fs::File,
+    io::{self, BufWriter, Write},
+    path::{Path, PathBuf},
+    time::{Duration, Instant},
+};
+
+use chrono::Local;
+
+/// The first line of the file, naming the columns.
+const HEADER: &str = "timestamp,elapsed_ms,task,progress";
+
+/// Longest a row waits in the buffer once another arrives, so a killed run
+/// loses little.
+const FLUSH_INTERVAL: Duration = Duration::from_secs(1);
+
+/// An open CSV file, written a row per update such as
+/// `2024-05-01T12:00:03.250,1250,Download,0.42`.
+pub struct Timeline {
+    path: PathBuf,
+    writer: BufWriter<File>,
+    start: Instant,
+    flushed_at: Instant,
+}
+
+impl Timeline {
+    /// Creates `path`, emptying it if it exists, and writes the header, so a
+    /// bad path fails before the run rather than after it.
+    pub fn create(path: &Path) -> io::Result<Self> {
+        let mut writer = BufWriter::new(File::create(path)?);
+        writeln!(writer, "{HEADER}")?;
+        writer.flush()?;
+        let now = Instant::now();
+        Ok(Self {
+            path: path.to_path_buf(),
+            writer,
+            start: now,
+            flushed_at: now,
+        })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Appends a row for the task called `name` reaching `progress` now.
+    pub fn record(&mut self, name: &str, progress: f64) -> io::Result<()> {
+        let now = Instant::now();
+        writeln!(
+            self.writer,
+            "{},{},{},{progress}",
+            Local::now().format("%Y-%m-%dT%H:%M:%S%.3f"),
+            now.duration_since(self.start).as_millis(),
+            csv_field(name)
+        )?;
+        if now.duration_since(self.flushed_at) < FLUSH_INTERVAL {
+            return Ok(());
+        }
+        self.flush()
+    }
+
+    /// Writes out the rows still in the buffer.
+    pub fn flush(&mut self) -> io::Result<()> {
+        self.flushed_at = Instant::now();
+        self.writer.flush()
+    }
+}
+
+/// Quotes `text` if it holds a comma, quote or line break, as task names
+/// taken from a command line may.
+fn csv_field(text: &str) -> Cow<'_, str> {
+    if text.contains([',', '"', '\n', '\r']) {
+        Cow::Owned(format!("\"{}\"", text.replace('"', "\"\"")))
+    } else {
+        Cow::Borrowed(text)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rows_follow_a_header_with_odd_task_names_quoted() {
+        let path = std::env::temp_dir().join(format!("timeline-{}.csv", std::process::id()));
+        let mut timeline = Timeline::create(&path).unwrap();
+        // The header is there before any update.
+        assert_eq!(
+            std::fs::read_to_string(&path).unwrap(),
+            format!("{HEADER}\n")
+        );
+        timeline.record("Download", 0.25).unwrap();
+        timeline.record("echo \"a, b\"", 0.5).unwrap();
+        timeline.record("Download", 1.0).unwrap();
+        timeline.flush().unwrap();
+
+        let contents = std::fs::read_to_string(&path).unwrap();
+        let mut lines = contents.lines();
+        assert_eq!(lines.next(), Some(HEADER));
+        let rows: Vec<Vec<&str>> = lines.map(|line| line.splitn(3, ',').collect()).collect();
+        assert_eq!(rows.len(), 3);
+        // An ISO 8601 time to the millisecond, then the time since the start.
+        assert_eq!(rows[0][0].len(), "2024-05-01T12:00:03.250".len());
+        assert_eq!(rows[0][0].as_bytes()[10], b'T');
+        assert!(rows[0][1].parse::<u128>().is_ok());
+        assert_eq!(rows[0][2], "Download,0.25");
+        assert_eq!(rows[1][2], "\"echo \"\"a, b\"\"\",0.5");
+        assert_eq!(rows[2][2], "Download,1");
+        std::fs::remove_file(&path).unwrap();
+    }
+}
@@ -0,0 +1,63 @@
+use std::sync::mpsc;
+
+use crossterm::event::{KeyCode, KeyEvent};
+use progress_tracker::{Event, ProgressApp};
+use ratatui::{Terminal, backend::TestBackend, style::Color};
+
+fn screen(terminal: &Terminal<TestBackend>) -> String {
+    let buffer = terminal.backend().buffer();
+    buffer.content.iter().map(|cell| cell.symbol()).collect()
+}
+
+#[test]
+fn run_draws_progress_sent_over_the_channel() {
+    let mut app = ProgressApp::new(["Build", "Test"]);
+    app.set_progress(1, 0.25);
+    app.toggle_color();
+
+    let (tx, rx) = mpsc::channel();
+    tx.send(Event::Progress(0, 0.5)).unwrap();
+    drop(tx);
+    let mut terminal = Terminal::new(TestBackend::new(80, 20)).unwrap();
+    app.run(&mut terminal, rx).unwrap();
+
+    // The run loop exits once every sender is gone, before drawing again.
+    terminal
+        .draw(|frame| frame.render_widget(&app, frame.area()))
+        .unwrap();
+    let screen = screen(&terminal);
+    assert!(screen.contains("Build"));
+    assert!(screen.contains("50%"));
+    assert!(screen.contains("25%"));
+}
+
+#[test]
+fn run_plays_a_scripted_session_through_to_quitting() {
+    let key = |c| Event::Input(KeyEvent::from(KeyCode::Char(c)));
+    let (tx, rx) = mpsc::channel();
+    for event in [
+        Event::Progress(0, 0.25),
+        Event::Progress(1, 1.0),
+        Event::Progress(0, 0.5),
+        key('c'),
+        key('q'),
+        key('y'),
+        // Nothing after the confirmed quit is handled.
+        Event::Progress(0, 0.75),
+    ] {
+        tx.send(event).unwrap();
+    }
+
+    let mut app = ProgressApp::new(["Build", "Test"]);
+    let mut terminal = Terminal::new(TestBackend::new(80, 20)).unwrap();
+    // The sender is still open, so only the keys can end the run.
+    app.run(&mut terminal, rx).unwrap();
+
+    assert!(app.has_exited());
+    assert_eq!(app.progress(0), Some(0.5));
+    assert_eq!(app.progress(1), Some(1.0));
+    assert_eq!(app.gauge_color(0), Some(Color::Yellow));
+    assert_eq!(app.gauge_color(1), Some(Color::Green));
+    assert!(screen(&terminal).contains("Build"));
+    drop(tx);
+}